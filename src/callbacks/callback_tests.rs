@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Dtype,
+        callbacks::{
+            Callback,
+            early_stopping::EarlyStopping,
+            model_checkpoint::{ModelCheckpoint, Monitor},
+            reduce_lr_on_plateau::ReduceLrOnPlateau,
+            training_context::{CallbackAction, TrainingContext},
+        },
+        networks::{
+            history::{LOSS, Logs, TrainingMetric},
+            network::Network,
+            test_fixtures::{batch, small_network},
+        },
+    };
+
+    // Epoch logs with the given validation loss.
+    fn logs(val_loss: Dtype) -> Logs {
+        TrainingMetric {
+            epoch: 1,
+            loss: 0.0,
+            accuracy: 0.0,
+            val_loss: Some(val_loss),
+            val_accuracy: Some(0.5),
+        }
+        .to_logs()
+    }
+
+    // Context of `on_epoch_end` for the given epoch.
+    fn epoch_end(epoch: usize, logs: Logs) -> TrainingContext {
+        TrainingContext {
+            epoch,
+            batch: 1,
+            step: epoch,
+            learning_rates: Vec::new(),
+            logs,
+        }
+    }
+
+    #[test]
+    fn test_reduce_lr_on_plateau() {
+        let mut net = small_network(0.1);
+        let mut callback = ReduceLrOnPlateau::new(2, 0.5, 1, 0.02, 0.0);
+        callback.on_train_begin(&mut net);
+
+        // The loss stops improving after the first epoch.
+        let rates = (1..=7)
+            .map(|epoch| {
+                callback.on_epoch_end(&mut net, &epoch_end(epoch, logs(1.0)));
+                net.learning_rates()[0].unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(rates, vec![0.1, 0.1, 0.05, 0.05, 0.05, 0.025, 0.025]);
+
+        // Never drops below min_lr.
+        (8..=13).for_each(|epoch| {
+            callback.on_epoch_end(&mut net, &epoch_end(epoch, logs(1.0)));
+        });
+        assert_eq!(net.learning_rates()[0], Some(0.02));
+    }
+
+    #[test]
+    fn test_model_checkpoint_keeps_best_and_last_snapshots() {
+        let mut net = small_network(0.1);
+        let (x, _) = batch();
+        let dir = std::env::temp_dir().join("neural_networks_model_checkpoint");
+        std::fs::create_dir_all(&dir).unwrap();
+        let best_path = dir.join("best.json");
+        let template = dir.join("epoch_{epoch}.json");
+
+        let mut callback = ModelCheckpoint::new(&best_path, Monitor::ValLoss).with_snapshots(
+            2,
+            template.to_str().unwrap(),
+            2,
+        );
+        callback.on_train_begin(&mut net);
+        (1..=7).for_each(|epoch| {
+            callback.on_epoch_end(&mut net, &epoch_end(epoch, logs(1.0)));
+        });
+
+        let snapshots = |epoch: usize| dir.join(format!("epoch_{}.json", epoch)).exists();
+        let existing = (1..=7).filter(|e| snapshots(*e)).collect::<Vec<_>>();
+        let best_exists = best_path.exists();
+        let best = Network::load(&best_path).map(|mut loaded| loaded.forward(&x));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(existing, vec![4, 6]);
+        assert!(best_exists);
+        assert_eq!(best.unwrap().data, net.forward(&x).data);
+    }
+
+    #[test]
+    fn test_early_stopping_restores_best_weights() {
+        let mut net = small_network(0.1);
+        let best = net.layers[0].get_weights().unwrap().clone();
+
+        let mut callback = EarlyStopping::new(2, 0.0).restore_best_weights(true);
+        callback.on_train_begin(&mut net);
+        assert_eq!(
+            callback.on_epoch_end(&mut net, &epoch_end(1, logs(0.5))),
+            CallbackAction::Continue
+        );
+        for parameter in net.layers[0].parameters_mut() {
+            parameter.value.data.fill(1.0);
+        }
+        assert_eq!(
+            callback.on_epoch_end(&mut net, &epoch_end(2, logs(0.7))),
+            CallbackAction::Continue
+        );
+        assert_eq!(
+            callback.on_epoch_end(&mut net, &epoch_end(3, logs(0.6))),
+            CallbackAction::Stop
+        );
+        assert_eq!(callback.stopped_epoch, 3);
+
+        callback.on_train_end(&mut net);
+        assert_eq!(net.layers[0].get_weights().unwrap().data, best.data);
+    }
+
+    #[test]
+    fn test_callbacks_keep_ema_weights_when_evaluating_with_ema() {
+        let (x, y) = batch();
+        let mut net = small_network(0.1);
+        net.enable_ema(0.5);
+        net.train(&x, &y, None, 3, 2).unwrap();
+        let ema_output = net.with_ema_weights(|net| net.forward(&x));
+        assert_ne!(ema_output.data, net.forward(&x).data);
+
+        let best_path = std::env::temp_dir().join("neural_networks_ema_checkpoint.json");
+        let mut checkpoint = ModelCheckpoint::new(&best_path, Monitor::ValLoss);
+        checkpoint.on_train_begin(&mut net);
+        checkpoint.on_epoch_end(&mut net, &epoch_end(1, logs(0.5)));
+        let saved = Network::load(&best_path).map(|mut loaded| loaded.forward(&x));
+        std::fs::remove_file(&best_path).unwrap();
+        assert_eq!(saved.unwrap().data, ema_output.data);
+
+        let mut early_stopping = EarlyStopping::new(1, 0.0).restore_best_weights(true);
+        early_stopping.on_train_begin(&mut net);
+        early_stopping.on_epoch_end(&mut net, &epoch_end(1, logs(0.5)));
+        net.train(&x, &y, None, 2, 2).unwrap();
+        assert_eq!(
+            early_stopping.on_epoch_end(&mut net, &epoch_end(2, logs(0.7))),
+            CallbackAction::Stop
+        );
+        early_stopping.on_train_end(&mut net);
+
+        assert_eq!(net.forward(&x).data, ema_output.data);
+        assert_eq!(
+            net.with_ema_weights(|net| net.forward(&x)).data,
+            ema_output.data
+        );
+    }
+
+    #[test]
+    fn test_swa_weights_win_over_early_stopping_restore() {
+        let (x, y) = batch();
+        let train = |epochs: usize, early_stopping: bool| {
+            let mut net = small_network(0.1);
+            net.enable_swa(1);
+            if early_stopping {
+                // Only the first epoch counts as an improvement, so training stops after two.
+                net.add_callback(EarlyStopping::new(1, 1e9).restore_best_weights(true));
+            }
+            net.train(&x, &y, Some((&x, &y)), epochs, 2).unwrap();
+            net.forward(&x).data
+        };
+
+        let stopped = train(10, true);
+        assert_eq!(stopped, train(2, false));
+        assert_ne!(stopped, train(1, false));
+    }
+
+    #[test]
+    fn test_callbacks_ignore_epochs_without_validation_metrics() {
+        let mut net = small_network(0.1);
+        let mut logs = Logs::new();
+        logs.insert(LOSS.to_string(), 0.3);
+
+        let mut early_stopping = EarlyStopping::new(1, 0.0);
+        let mut reduce_lr = ReduceLrOnPlateau::new(1, 0.5, 0, 0.0, 0.0);
+        for epoch in 1..=3 {
+            assert_eq!(
+                early_stopping.on_epoch_end(&mut net, &epoch_end(epoch, logs.clone())),
+                CallbackAction::Continue
+            );
+            reduce_lr.on_epoch_end(&mut net, &epoch_end(epoch, logs.clone()));
+        }
+        assert_eq!(net.learning_rates()[0], Some(0.1));
+    }
+}
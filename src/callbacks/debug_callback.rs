@@ -1,4 +1,10 @@
-use crate::{callbacks::Callback, data_structures::matrix::Matrix, networks::network::Network};
+use crate::{
+    callbacks::{
+        Callback,
+        training_context::{CallbackAction, TrainingContext},
+    },
+    networks::network::Network,
+};
 
 
 #[derive(Default)]
@@ -12,8 +18,15 @@ impl DebugCallback {
 }
 
 impl Callback for DebugCallback {
-    fn on_epoch_end(&mut self, _network: &mut Network, _y_pred: &Matrix, _y_true: &Matrix) -> bool {
-        false
+    fn on_epoch_end(
+        &mut self,
+        network: &mut Network,
+        _context: &TrainingContext,
+    ) -> CallbackAction {
+        if let Some(norm) = network.last_gradient_norm() {
+            log::debug!("gradient norm (before clipping): {:.6}", norm);
+        }
+        CallbackAction::Continue
     }
 
     fn on_train_end(&mut self, network: &mut Network) {
@@ -1,58 +1,102 @@
 use crate::{
-    Dtype, callbacks::Callback, data_structures::matrix::Matrix, networks::network::Network,
+    Dtype,
+    callbacks::{
+        Callback,
+        training_context::{CallbackAction, TrainingContext},
+    },
+    data_structures::matrix::Matrix,
+    networks::{history::VAL_LOSS, network::Network},
 };
 
+/// Stops training once the validation loss (`VAL_LOSS` in the logs) has not improved for
+/// `patience` epochs. Needs validation data passed to `Network::train`.
 pub struct EarlyStopping {
     pub patience: usize,
     pub min_delta: Dtype, // minimum improvement to reset counter
     best_loss: Dtype,
     wait: usize,
     pub stopped_epoch: usize,
-    x_valid: Matrix,
-    y_valid: Matrix,
+    stopped: bool,
+    restore_best_weights: bool,
+    best_weights: Option<Vec<Matrix>>,
 }
 
 impl EarlyStopping {
-    pub fn new(patience: usize, min_delta: Dtype, x_valid: &Matrix, y_valid: &Matrix) -> Self {
+    pub fn new(patience: usize, min_delta: Dtype) -> Self {
         EarlyStopping {
             patience,
             min_delta,
             best_loss: Dtype::INFINITY,
             wait: 0,
             stopped_epoch: 0,
-            x_valid: x_valid.clone(),
-            y_valid: y_valid.clone(),
+            stopped: false,
+            restore_best_weights: false,
+            best_weights: None,
         }
     }
+
+    /// Keeps a copy of the parameters with the best validation loss and loads it back into
+    /// the network at the end of training if early stopping was triggered. With evaluation on
+    /// EMA weights, the EMA weights are kept.
+    pub fn restore_best_weights(mut self, enabled: bool) -> EarlyStopping {
+        self.restore_best_weights = enabled;
+        self
+    }
 }
 
 impl Callback for EarlyStopping {
-    fn on_train_end(&mut self, _network: &mut Network) {}
+    fn on_train_end(&mut self, network: &mut Network) {
+        // Restored here rather than when stopping, as the current epoch still runs to its end.
+        if self.stopped
+            && let Some(best_weights) = self.best_weights.take()
+        {
+            log::info!(
+                "Restoring weights with validation loss {:.6}",
+                self.best_loss
+            );
+            network.set_parameter_values(&best_weights);
+            // With EMA evaluation these are EMA weights, so the shadow copy is reset to them too.
+            network.with_evaluation_weights(|net| net.set_parameter_values(&best_weights));
+        }
+    }
 
-    fn on_epoch_end(&mut self, net: &mut Network, _y_pred: &Matrix, _y_true: &Matrix) -> bool {
-        let (val_loss, _val_accuracy) = net.validate(&self.x_valid, &self.y_valid); // or pass validation set
+    fn on_epoch_end(&mut self, net: &mut Network, context: &TrainingContext) -> CallbackAction {
+        let Some(&val_loss) = context.logs.get(VAL_LOSS) else {
+            log::warn!(
+                "EarlyStopping needs validation data, skipping epoch {}",
+                context.epoch
+            );
+            return CallbackAction::Continue;
+        };
 
         if val_loss + self.min_delta < self.best_loss {
             self.best_loss = val_loss;
             self.wait = 0;
+            if self.restore_best_weights {
+                // The weights the validation loss was computed with.
+                self.best_weights = Some(net.with_evaluation_weights(|net| net.parameter_values()));
+            }
         } else {
             self.wait += 1;
             if self.wait >= self.patience {
-                self.stopped_epoch += 1;
+                self.stopped_epoch = context.epoch;
+                self.stopped = true;
                 log::info!(
                     "Early stopping triggered at epoch {}. Best validation loss: {:.6}",
                     self.stopped_epoch,
                     self.best_loss
                 );
-                return true;
+                return CallbackAction::Stop;
             }
         }
 
-        return false;
+        CallbackAction::Continue
     }
 
-    fn on_train_begin(&mut self) {
+    fn on_train_begin(&mut self, _network: &mut Network) {
         self.best_loss = Dtype::INFINITY;
         self.wait = 0;
+        self.stopped = false;
+        self.best_weights = None;
     }
 }
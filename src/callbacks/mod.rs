@@ -1,15 +1,62 @@
-use crate::{data_structures::matrix::Matrix, networks::network::Network};
+use crate::{
+    callbacks::training_context::{CallbackAction, TrainingContext},
+    networks::network::Network,
+};
 
+pub mod callback_tests;
 pub mod plotting_callback;
 pub mod debug_callback;
 pub mod early_stopping;
+pub mod model_checkpoint;
+pub mod reduce_lr_on_plateau;
+pub mod training_context;
 
 pub trait Callback: Send {
-    /// Called at the start of training.
-    fn on_train_begin(&mut self) {}
+    /// Called at the start of training, before the first epoch.
+    fn on_train_begin(&mut self, _network: &mut Network) {}
 
-    /// Called at the end of every epoch. The callback is responsible for calculating metrics.
-    fn on_epoch_end(&mut self, network: &mut Network, y_pred: &Matrix, y_true: &Matrix) -> bool;
+    /// Called before every epoch's batches. `Skip` leaves the epoch out entirely, `Stop` ends
+    /// training without running it.
+    fn on_epoch_begin(
+        &mut self,
+        _network: &mut Network,
+        _context: &TrainingContext,
+    ) -> CallbackAction {
+        CallbackAction::Continue
+    }
+
+    /// Called before every batch. `Skip` leaves the batch out of training and the metrics.
+    fn on_batch_begin(
+        &mut self,
+        _network: &mut Network,
+        _context: &TrainingContext,
+    ) -> CallbackAction {
+        CallbackAction::Continue
+    }
+
+    /// Called after every batch's backward pass, with the batch metrics in the context's logs.
+    /// With gradient accumulation, the optimizer step may still be pending.
+    fn on_batch_end(
+        &mut self,
+        _network: &mut Network,
+        _context: &TrainingContext,
+    ) -> CallbackAction {
+        CallbackAction::Continue
+    }
+
+    /// Called after the validation at the end of an epoch, only if `train` was given validation
+    /// data. `Skip` has no effect here.
+    fn on_validation_end(
+        &mut self,
+        _network: &mut Network,
+        _context: &TrainingContext,
+    ) -> CallbackAction {
+        CallbackAction::Continue
+    }
+
+    /// Called at the end of every epoch that trained on at least one sample, with the epoch's
+    /// metrics in the context's logs, the same for every callback. `Skip` has no effect here.
+    fn on_epoch_end(&mut self, network: &mut Network, context: &TrainingContext) -> CallbackAction;
 
     /// Called at the end of training. The callback can use the network for final analysis.
     fn on_train_end(&mut self, network: &mut Network);
@@ -0,0 +1,174 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use crate::{
+    Dtype,
+    callbacks::{
+        Callback,
+        training_context::{CallbackAction, TrainingContext},
+    },
+    networks::{
+        history::{Logs, VAL_ACCURACY, VAL_LOSS},
+        network::Network,
+    },
+};
+
+/// Validation metric watched by `ModelCheckpoint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Monitor {
+    /// Lower is better.
+    ValLoss,
+    /// Higher is better.
+    ValAccuracy,
+}
+
+impl Monitor {
+    /// Name of the metric in the epoch logs.
+    pub fn key(self) -> &'static str {
+        match self {
+            Monitor::ValLoss => VAL_LOSS,
+            Monitor::ValAccuracy => VAL_ACCURACY,
+        }
+    }
+}
+
+/// Saves the model (see `Network::save`) whenever the monitored validation metric improves,
+/// and optionally a snapshot every `every_n_epochs` epochs, keeping only the newest ones.
+/// The saved weights are the ones validated, i.e. the EMA weights with evaluation on EMA.
+/// Needs validation data passed to `Network::train`.
+pub struct ModelCheckpoint {
+    pub monitor: Monitor,
+    pub min_delta: Dtype, // minimum improvement to count as a new best
+    best_path: PathBuf,
+    best: Dtype,
+    snapshots: Option<Snapshots>,
+}
+
+struct Snapshots {
+    every_n_epochs: usize,
+    template: String,
+    keep_last: usize,
+    written: VecDeque<PathBuf>,
+}
+
+impl ModelCheckpoint {
+    pub fn new<P: Into<PathBuf>>(best_path: P, monitor: Monitor) -> Self {
+        ModelCheckpoint {
+            monitor,
+            min_delta: 0.0,
+            best_path: best_path.into(),
+            best: Self::worst(monitor),
+            snapshots: None,
+        }
+    }
+
+    /// Additionally saves a snapshot every `every_n_epochs` epochs and deletes all but the
+    /// newest `keep_last` of them. In `template`, `{epoch}`, `{val_loss}` and `{val_accuracy}`
+    /// are replaced by their values, e.g. `"snapshots/epoch_{epoch}.json"`.
+    pub fn with_snapshots(
+        mut self,
+        every_n_epochs: usize,
+        template: &str,
+        keep_last: usize,
+    ) -> Self {
+        assert!(every_n_epochs > 0, "every_n_epochs must be positive");
+        assert!(keep_last > 0, "keep_last must be positive");
+        self.snapshots = Some(Snapshots {
+            every_n_epochs,
+            template: template.to_string(),
+            keep_last,
+            written: VecDeque::new(),
+        });
+        self
+    }
+
+    /// Best value of the monitored metric seen so far.
+    pub fn best(&self) -> Dtype {
+        self.best
+    }
+
+    fn worst(monitor: Monitor) -> Dtype {
+        match monitor {
+            Monitor::ValLoss => Dtype::INFINITY,
+            Monitor::ValAccuracy => Dtype::NEG_INFINITY,
+        }
+    }
+
+    fn is_improvement(&self, value: Dtype) -> bool {
+        match self.monitor {
+            Monitor::ValLoss => value + self.min_delta < self.best,
+            Monitor::ValAccuracy => value - self.min_delta > self.best,
+        }
+    }
+
+    fn save_if_improved(&mut self, net: &mut Network, value: Dtype) {
+        if self.is_improvement(value) {
+            log::info!(
+                "{:?} improved from {:.6} to {:.6}, saving model to {}",
+                self.monitor,
+                self.best,
+                value,
+                self.best_path.display()
+            );
+            self.best = value;
+            if let Err(e) = net.with_evaluation_weights(|net| net.save(&self.best_path)) {
+                log::error!("Failed to save best model: {:#}", e);
+            }
+        }
+    }
+}
+
+impl Snapshots {
+    fn path(&self, epoch: usize, logs: &Logs) -> PathBuf {
+        let metric = |key: &str| logs.get(key).copied().unwrap_or(Dtype::NAN);
+        PathBuf::from(
+            self.template
+                .replace("{epoch}", &epoch.to_string())
+                .replace("{val_loss}", &format!("{:.4}", metric(VAL_LOSS)))
+                .replace("{val_accuracy}", &format!("{:.4}", metric(VAL_ACCURACY))),
+        )
+    }
+
+    fn save(&mut self, net: &mut Network, path: PathBuf) {
+        if let Err(e) = net.with_evaluation_weights(|net| net.save(&path)) {
+            log::error!("Failed to save snapshot: {:#}", e);
+            return;
+        }
+
+        self.written.retain(|written| *written != path);
+        self.written.push_back(path);
+        while self.written.len() > self.keep_last {
+            let old = self.written.pop_front().unwrap();
+            if let Err(e) = std::fs::remove_file(&old) {
+                log::warn!("Failed to remove old snapshot {}: {}", old.display(), e);
+            }
+        }
+    }
+}
+
+impl Callback for ModelCheckpoint {
+    fn on_train_begin(&mut self, _network: &mut Network) {
+        self.best = Self::worst(self.monitor);
+    }
+
+    fn on_epoch_end(&mut self, net: &mut Network, context: &TrainingContext) -> CallbackAction {
+        let (epoch, logs) = (context.epoch, &context.logs);
+        match logs.get(self.monitor.key()) {
+            Some(&value) => self.save_if_improved(net, value),
+            None => log::warn!(
+                "ModelCheckpoint needs validation data, not checking epoch {}",
+                epoch
+            ),
+        }
+
+        if let Some(snapshots) = self.snapshots.as_mut()
+            && epoch.is_multiple_of(snapshots.every_n_epochs)
+        {
+            let path = snapshots.path(epoch, logs);
+            snapshots.save(net, path);
+        }
+
+        CallbackAction::Continue
+    }
+
+    fn on_train_end(&mut self, _network: &mut Network) {}
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use plotters::{
     chart::{ChartBuilder, SeriesLabelPosition},
     prelude::{BitMapBackend, IntoDrawingArea, PathElement},
@@ -7,29 +9,34 @@ use plotters::{
 
 use crate::{
     Dtype,
-    callbacks::Callback,
-    data_structures::matrix::Matrix,
-    networks::network::{Network, TrainingMetric},
+    callbacks::{
+        Callback,
+        training_context::{CallbackAction, TrainingContext},
+    },
+    networks::{history::TrainingMetric, network::Network},
 };
 
+/// Plots loss and accuracy at the end of training as `<dir>/<prefix>loss.png` and
+/// `<dir>/<prefix>accuracy.png`.
 pub struct PlottingCallback {
     pub metrics: Vec<TrainingMetric>,
-    pub output_path: String,
+    pub dir: PathBuf,
+    pub prefix: String,
 }
 
 impl PlottingCallback {
-    pub fn new(output_path: &str) -> Self {
+    pub fn new<P: Into<PathBuf>>(dir: P, prefix: &str) -> Self {
         PlottingCallback {
             metrics: Vec::new(),
-            output_path: output_path.to_string(),
+            dir: dir.into(),
+            prefix: prefix.to_string(),
         }
     }
 
     /// Internal method to handle the plotting logic.
     fn plot_metrics_internal(&self) -> anyhow::Result<()> {
-        let dir = "/home/xhatalc/pv021_project/src/neural-networks/src/plots";
-        std::fs::create_dir_all(dir)?;
-        let dir_path = std::path::Path::new(dir);
+        std::fs::create_dir_all(&self.dir)?;
+        let dir_path = self.dir.as_path();
 
         let metrics = &self.metrics;
 
@@ -45,9 +52,9 @@ impl PlottingCallback {
 
         // === Chart 1: LOSS =======================================================
         {
-            let file_path = dir_path.join(format!("{}loss.png", self.output_path));
+            let file_path = dir_path.join(format!("{}loss.png", self.prefix));
             let root =
-                BitMapBackend::new(file_path.to_str().unwrap(), (800, 600)).into_drawing_area();
+                BitMapBackend::new(&file_path, (800, 600)).into_drawing_area();
             root.fill(&WHITE)?;
 
             let mut chart = ChartBuilder::on(&root)
@@ -67,29 +74,26 @@ impl PlottingCallback {
             chart
                 .draw_series(LineSeries::new(
                     metrics.iter().map(|m| (m.epoch, m.loss)),
-                    &RED.mix(0.8),
+                    RED.mix(0.8),
                 ))?
                 .label("Loss")
-                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED.mix(0.8)));
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED.mix(0.8)));
 
             chart
                 .configure_series_labels()
                 .position(SeriesLabelPosition::UpperLeft)
-                .border_style(&BLACK)
-                .background_style(&WHITE.mix(0.8))
+                .border_style(BLACK)
+                .background_style(WHITE.mix(0.8))
                 .draw()?;
 
-            log::info!(
-                "Saved loss chart at {}",
-                dir_path.join("loss.png").to_str().unwrap()
-            );
+            log::info!("Saved loss chart at {}", file_path.display());
         }
 
         // === Chart 2: ACCURACY ===================================================
         {
-            let file_path = dir_path.join(format!("{}accuracy.png", self.output_path));
+            let file_path = dir_path.join(format!("{}accuracy.png", self.prefix));
             let root =
-                BitMapBackend::new(file_path.to_str().unwrap(), (800, 600)).into_drawing_area();
+                BitMapBackend::new(&file_path, (800, 600)).into_drawing_area();
             root.fill(&WHITE)?;
 
             let mut chart = ChartBuilder::on(&root)
@@ -109,22 +113,19 @@ impl PlottingCallback {
             chart
                 .draw_series(LineSeries::new(
                     metrics.iter().map(|m| (m.epoch, m.accuracy)),
-                    &BLUE.mix(0.8),
+                    BLUE.mix(0.8),
                 ))?
                 .label("Accuracy")
-                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE.mix(0.8)));
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE.mix(0.8)));
 
             chart
                 .configure_series_labels()
                 .position(SeriesLabelPosition::UpperLeft)
-                .border_style(&BLACK)
-                .background_style(&WHITE.mix(0.8))
+                .border_style(BLACK)
+                .background_style(WHITE.mix(0.8))
                 .draw()?;
 
-            log::info!(
-                "Saved accuracy chart at {}",
-                dir_path.join("accuracy.png").to_str().unwrap()
-            );
+            log::info!("Saved accuracy chart at {}", file_path.display());
         }
 
         Ok(())
@@ -132,18 +133,16 @@ impl PlottingCallback {
 }
 
 impl Callback for PlottingCallback {
-    fn on_epoch_end(&mut self, network: &mut Network, y_pred: &Matrix, y_true: &Matrix) -> bool {
-        // Calculate metrics using the current predictions stored in the network
-        let loss = network.calculate_loss(y_pred, y_true);
-        let accuracy = network.calculate_accuracy(y_pred, y_true);
-
-        let metric = TrainingMetric {
-            epoch: self.metrics.len() as u32, // Simple epoch counter
-            loss,
-            accuracy,
-        };
-        self.metrics.push(metric);
-        return false;
+    fn on_epoch_end(
+        &mut self,
+        _network: &mut Network,
+        context: &TrainingContext,
+    ) -> CallbackAction {
+        self.metrics.push(TrainingMetric::from_logs(
+            context.epoch as u32,
+            &context.logs,
+        ));
+        CallbackAction::Continue
     }
 
     // Now accepts the mutable network reference but ignores it for plotting
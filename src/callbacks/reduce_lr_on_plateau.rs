@@ -0,0 +1,94 @@
+use crate::{
+    Dtype,
+    callbacks::{
+        Callback,
+        training_context::{CallbackAction, TrainingContext},
+    },
+    networks::{history::VAL_LOSS, network::Network},
+};
+
+/// Multiplies the learning rate of every optimizer by `factor` once the validation loss
+/// has not improved for `patience` epochs. Needs validation data passed to `Network::train`.
+pub struct ReduceLrOnPlateau {
+    pub patience: usize,
+    pub factor: Dtype,
+    pub cooldown: usize, // epochs to wait after a reduction before counting again
+    pub min_lr: Dtype,
+    pub min_delta: Dtype, // minimum improvement to reset counter
+    best_loss: Dtype,
+    wait: usize,
+    cooldown_counter: usize,
+}
+
+impl ReduceLrOnPlateau {
+    pub fn new(
+        patience: usize,
+        factor: Dtype,
+        cooldown: usize,
+        min_lr: Dtype,
+        min_delta: Dtype,
+    ) -> Self {
+        assert!(
+            factor > 0.0 && factor < 1.0,
+            "factor must be in (0, 1), got {}",
+            factor
+        );
+
+        ReduceLrOnPlateau {
+            patience,
+            factor,
+            cooldown,
+            min_lr,
+            min_delta,
+            best_loss: Dtype::INFINITY,
+            wait: 0,
+            cooldown_counter: 0,
+        }
+    }
+}
+
+impl Callback for ReduceLrOnPlateau {
+    fn on_train_begin(&mut self, _network: &mut Network) {
+        self.best_loss = Dtype::INFINITY;
+        self.wait = 0;
+        self.cooldown_counter = 0;
+    }
+
+    fn on_epoch_end(&mut self, net: &mut Network, context: &TrainingContext) -> CallbackAction {
+        let Some(&val_loss) = context.logs.get(VAL_LOSS) else {
+            log::warn!(
+                "ReduceLrOnPlateau needs validation data, skipping epoch {}",
+                context.epoch
+            );
+            return CallbackAction::Continue;
+        };
+
+        let in_cooldown = self.cooldown_counter > 0;
+        if in_cooldown {
+            self.cooldown_counter -= 1;
+            self.wait = 0;
+        }
+
+        if val_loss + self.min_delta < self.best_loss {
+            self.best_loss = val_loss;
+            self.wait = 0;
+        } else if !in_cooldown {
+            self.wait += 1;
+            if self.wait >= self.patience {
+                let (factor, min_lr) = (self.factor, self.min_lr);
+                net.update_learning_rates(|lr| (lr * factor).max(min_lr).min(lr));
+                log::info!(
+                    "Validation loss plateaued at {:.6}, reducing learning rates to {:?}",
+                    self.best_loss,
+                    net.learning_rates().into_iter().flatten().collect::<Vec<_>>()
+                );
+                self.cooldown_counter = self.cooldown;
+                self.wait = 0;
+            }
+        }
+
+        CallbackAction::Continue
+    }
+
+    fn on_train_end(&mut self, _network: &mut Network) {}
+}
@@ -0,0 +1,32 @@
+use crate::{Dtype, networks::history::Logs};
+
+/// State of a running `Network::train`, passed to the batch and epoch hooks of `Callback`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingContext {
+    /// Current epoch, 1-based.
+    pub epoch: usize,
+    /// Index of the current batch within the epoch. Epoch-level hooks get the number of
+    /// batches run so far in the epoch.
+    pub batch: usize,
+    /// Number of optimizer steps taken so far, over all epochs.
+    pub step: usize,
+    /// Learning rate of every layer's optimizer, `None` for layers without parameters.
+    pub learning_rates: Vec<Option<Dtype>>,
+    /// Metrics known at this point: the batch's `LOSS` and `ACCURACY` in `on_batch_end`,
+    /// the epoch's metrics in `on_validation_end` and `on_epoch_end`, empty otherwise.
+    pub logs: Logs,
+}
+
+/// What a hook asks `Network::train` to do next. If several callbacks disagree, `Stop` wins
+/// over `Skip`, which wins over `Continue`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CallbackAction {
+    #[default]
+    Continue,
+    /// Skips the rest of the current unit of work: the epoch in `on_epoch_begin`, the batch
+    /// in `on_batch_begin` and the remaining batches of the epoch in `on_batch_end`.
+    Skip,
+    /// Ends training. From `on_epoch_begin` the epoch does not run, from a batch hook the
+    /// epoch ends early, and from the later hooks training ends after the current epoch.
+    Stop,
+}
@@ -0,0 +1,222 @@
+//! Parsing of the command line: a subcommand with `--name value` (or `--name=value`) options.
+//! The global options may appear anywhere.
+
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+
+use anyhow::{Context, anyhow};
+use neural_networks::Dtype;
+
+pub const USAGE: &str = "\
+Usage: neural-networks <command> [options]
+
+Commands:
+  train     Train a Fashion MNIST network
+              --config <file>          model spec (.json or .toml): layers, optimizer and
+                                       training settings
+              --experiment <name>      without --config: `mnist` (default) or `xor`
+  eval      Report loss and accuracy of a saved model
+              --model <file>           model written by `save` (.json) or `save_binary` (.bin)
+              --data <file.csv>        one sample per row
+              --labels <file.csv>      one class index per row
+              --scale <divisor>        inputs are divided by it, e.g. 255 for pixels (default 1)
+  predict   Write class probabilities, one CSV row per input row
+              --model <file>
+              --input <file.csv>       read from stdin if omitted
+              --output <file.csv>      write to stdout if omitted
+              --scale <divisor>
+  search    Grid search over Fashion MNIST hyperparameters
+              --space <file.json>      value lists per hyperparameter (default grid if omitted)
+  help      Show this message
+
+Global options:
+  --threads <n>         size of the rayon thread pool
+  --log-level <level>   off, error, warn, info (default), debug or trace
+  --data-root <dir>     directory with the dataset CSV files
+                        (env NEURAL_NETWORKS_DATA_ROOT, default ./data)
+  --output-dir <dir>    where models, checkpoints, plots and results are written
+                        (env NEURAL_NETWORKS_OUTPUT_DIR, default .)
+  --settings <file>     JSON file with `data_root` and `output_dir`, used when neither the
+                        flag nor the variable is set (default ./neural-networks.json)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Experiment {
+    FashionMnist,
+    Xor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Train {
+        config: Option<PathBuf>,
+        experiment: Experiment,
+    },
+    Eval {
+        model: PathBuf,
+        data: PathBuf,
+        labels: PathBuf,
+        scale: Dtype,
+    },
+    Predict {
+        model: PathBuf,
+        input: Option<PathBuf>,
+        output: Option<PathBuf>,
+        scale: Dtype,
+    },
+    Search {
+        space: Option<PathBuf>,
+    },
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub threads: Option<usize>,
+    pub log_level: Option<log::LevelFilter>,
+    pub data_root: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub settings: Option<PathBuf>,
+    pub command: Command,
+}
+
+/// The `--name value` options that were given, removed as the command consumes them.
+struct Options {
+    values: BTreeMap<String, String>,
+}
+
+impl Options {
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)
+    }
+
+    fn take_parsed<T>(&mut self, name: &str) -> anyhow::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.take(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .with_context(|| format!("invalid value '{}' for --{}", value, name))
+            })
+            .transpose()
+    }
+
+    fn require(&mut self, name: &str, command: &str) -> anyhow::Result<String> {
+        self.take(name)
+            .ok_or_else(|| anyhow!("'{}' needs --{}", command, name))
+    }
+
+    /// The `--scale` divisor, 1 if not given.
+    fn scale(&mut self) -> anyhow::Result<Dtype> {
+        let scale = self.take_parsed::<Dtype>("scale")?.unwrap_or(1.0);
+        if !scale.is_finite() || scale <= 0.0 {
+            return Err(anyhow!("--scale must be a positive number, got {}", scale));
+        }
+        Ok(scale)
+    }
+
+    /// Fails on options that no part of the command line used.
+    fn finish(self, command: &str) -> anyhow::Result<()> {
+        match self.values.keys().next() {
+            Some(name) => Err(anyhow!("unknown option --{} for '{}'", name, command)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses the arguments after the program name.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Cli> {
+    let mut command = None;
+    let mut help = false;
+    let mut values = BTreeMap::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            help = true;
+        } else if let Some(option) = arg.strip_prefix("--") {
+            let (name, value) = match option.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--{} needs a value", option))?;
+                    (option.to_string(), value)
+                }
+            };
+            if values.insert(name.clone(), value).is_some() {
+                return Err(anyhow!("--{} is given more than once", name));
+            }
+        } else if command.is_none() {
+            command = Some(arg);
+        } else {
+            return Err(anyhow!("unexpected argument '{}'", arg));
+        }
+    }
+
+    let mut options = Options { values };
+    let threads = options.take_parsed::<usize>("threads")?;
+    if threads == Some(0) {
+        return Err(anyhow!("--threads must be at least 1"));
+    }
+    let log_level = options.take_parsed("log-level")?;
+    let data_root = options.take("data-root").map(PathBuf::from);
+    let output_dir = options.take("output-dir").map(PathBuf::from);
+    let settings = options.take("settings").map(PathBuf::from);
+
+    let name = match command {
+        _ if help => "help".to_string(),
+        Some(name) => name,
+        None => return Err(anyhow!("no command given\n\n{}", USAGE)),
+    };
+    let command = match name.as_str() {
+        "train" => {
+            let config = options.take("config").map(PathBuf::from);
+            let experiment = match options.take("experiment").as_deref() {
+                None | Some("mnist") => Experiment::FashionMnist,
+                Some("xor") => Experiment::Xor,
+                Some(other) => {
+                    return Err(anyhow!(
+                        "unknown experiment '{}', expected 'mnist' or 'xor'",
+                        other
+                    ));
+                }
+            };
+            if config.is_some() && experiment != Experiment::FashionMnist {
+                return Err(anyhow!(
+                    "--config only applies to the Fashion MNIST network"
+                ));
+            }
+            Command::Train { config, experiment }
+        }
+        "eval" => Command::Eval {
+            model: options.require("model", &name)?.into(),
+            data: options.require("data", &name)?.into(),
+            labels: options.require("labels", &name)?.into(),
+            scale: options.scale()?,
+        },
+        "predict" => Command::Predict {
+            model: options.require("model", &name)?.into(),
+            input: options.take("input").map(PathBuf::from),
+            output: options.take("output").map(PathBuf::from),
+            scale: options.scale()?,
+        },
+        "search" => Command::Search {
+            space: options.take("space").map(PathBuf::from),
+        },
+        "help" => Command::Help,
+        other => return Err(anyhow!("unknown command '{}'\n\n{}", other, USAGE)),
+    };
+    if command != Command::Help {
+        options.finish(&name)?;
+    }
+
+    Ok(Cli {
+        threads,
+        log_level,
+        data_root,
+        output_dir,
+        settings,
+        command,
+    })
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::cli::{
+        args::{Command, Experiment, parse},
+        paths::{DATA_ROOT_VAR, OUTPUT_DIR_VAR, Paths, Settings},
+    };
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_train_with_global_options_anywhere() {
+        let cli = parse(args(
+            "--threads 4 train --config cfg.json --log-level=debug --output-dir runs",
+        ))
+        .unwrap();
+
+        assert_eq!(cli.threads, Some(4));
+        assert_eq!(cli.log_level, Some(log::LevelFilter::Debug));
+        assert_eq!(cli.output_dir, Some(PathBuf::from("runs")));
+        assert_eq!(
+            cli.command,
+            Command::Train {
+                config: Some(PathBuf::from("cfg.json")),
+                experiment: Experiment::FashionMnist,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_predict_defaults() {
+        let cli = parse(args("predict --model m.bin")).unwrap();
+
+        assert_eq!(cli.threads, None);
+        assert_eq!((cli.data_root, cli.output_dir), (None, None));
+        assert_eq!(
+            cli.command,
+            Command::Predict {
+                model: PathBuf::from("m.bin"),
+                input: None,
+                output: None,
+                scale: 1.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        // Missing required option, unknown option, unknown command, invalid value.
+        assert!(parse(args("eval --model m.bin --data x.csv")).is_err());
+        assert!(parse(args("search --space s.json --epochs 3")).is_err());
+        assert!(parse(args("fit")).is_err());
+        assert!(parse(args("train --threads 0")).is_err());
+        assert!(parse(args("predict --model m.bin --scale 0")).is_err());
+        assert!(parse(args("predict --model m.bin --scale=-255")).is_err());
+        assert!(parse(args("train --experiment xor --config cfg.json")).is_err());
+        assert!(parse(args("predict --model")).is_err());
+        assert!(parse(Vec::new()).is_err());
+
+        assert_eq!(parse(args("eval --help")).unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn test_paths_priority() {
+        let settings = || Settings {
+            data_root: Some(PathBuf::from("settings/data")),
+            output_dir: Some(PathBuf::from("settings/out")),
+        };
+        let env = |var: &str| match var {
+            DATA_ROOT_VAR => Some("env/data".to_string()),
+            _ => None,
+        };
+
+        // Flag over variable over settings file.
+        let paths = Paths::resolve(Some(PathBuf::from("flag/data")), None, env, settings());
+        assert_eq!(paths.data_root, PathBuf::from("flag/data"));
+        assert_eq!(paths.output_dir, PathBuf::from("settings/out"));
+
+        let paths = Paths::resolve(None, None, env, settings());
+        assert_eq!(paths.data_root, PathBuf::from("env/data"));
+        assert_eq!(paths.data("x.csv"), PathBuf::from("env/data/x.csv"));
+
+        let paths = Paths::resolve(None, None, |_| None, Settings::default());
+        assert_eq!(paths.data_root, PathBuf::from("data"));
+        assert_eq!(paths.plots(), PathBuf::from("./plots"));
+        assert_eq!(
+            Paths::resolve(
+                None,
+                None,
+                |var| (var == OUTPUT_DIR_VAR).then(String::new),
+                Settings::default()
+            )
+            .output_dir,
+            PathBuf::from(".")
+        );
+    }
+
+    #[test]
+    fn test_settings_paths_are_relative_to_the_file() {
+        let dir = std::env::temp_dir().join("neural_networks_settings");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{ "data_root": "datasets", "output_dir": "/tmp/runs" }"#,
+        )
+        .unwrap();
+
+        let settings = Settings::load(Some(&path)).unwrap();
+        assert_eq!(settings.data_root, Some(dir.join("datasets")));
+        assert_eq!(settings.output_dir, Some(PathBuf::from("/tmp/runs")));
+
+        std::fs::write(&path, r#"{ "data": "datasets" }"#).unwrap();
+        assert!(Settings::load(Some(&path)).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
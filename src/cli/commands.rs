@@ -0,0 +1,184 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+};
+
+use anyhow::{Context, anyhow};
+use neural_networks::{
+    Dtype, ModelSpec, Network,
+    data::load::{load_data, read_features},
+    initialize_rayon_pool,
+};
+
+use crate::{
+    cli::{
+        args::{Cli, Command, Experiment, USAGE},
+        paths::Paths,
+    },
+    grid_search::{
+        minst_config::train_mnist_with_spec,
+        train_config::{SearchSpace, run_grid_search},
+    },
+    training,
+};
+
+/// Number of samples passed through the network at once by `predict`.
+const PREDICT_BATCH_SIZE: usize = 1024;
+
+/// Loads a model saved with `save_binary` (`.bin`) or `save` (anything else).
+fn load_model(path: &Path) -> anyhow::Result<Network> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("bin") => Network::load_binary(path),
+        _ => Network::load(path),
+    }
+}
+
+fn model_sizes(net: &Network, path: &Path) -> anyhow::Result<(usize, usize)> {
+    net.input_size()
+        .zip(net.output_size())
+        .ok_or_else(|| anyhow!("{} has no layer with weights", path.display()))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn train(config: Option<&Path>, experiment: Experiment, paths: &Paths) -> anyhow::Result<()> {
+    let Some(config) = config else {
+        return match experiment {
+            Experiment::FashionMnist => {
+                log::info!("Starting Fashion MNIST training...");
+                training::fashionMNIST::train_mnist(paths)
+            }
+            Experiment::Xor => {
+                log::info!("Starting XOR training...");
+                training::xor::train_xor(paths)
+            }
+        };
+    };
+
+    let spec = ModelSpec::from_file(config)?;
+    let (net, accuracy) = train_mnist_with_spec(&spec, paths, None)?;
+    log::info!("Test accuracy: {:.2}%", accuracy * 100.0);
+    net.save(paths.output_dir.join("model.json"))?;
+    net.save_binary(paths.output_dir.join("model.bin"))
+}
+
+fn eval(model: &Path, data: &Path, labels: &Path, scale: Dtype) -> anyhow::Result<()> {
+    let mut net = load_model(model)?;
+    let (input_size, output_size) = model_sizes(&net, model)?;
+    let (x, y, _, _) = load_data(
+        &data.to_string_lossy(),
+        &labels.to_string_lossy(),
+        input_size,
+        output_size,
+        0.0,
+    )?;
+    let (loss, accuracy) = net.validate(&(&x / scale), &y);
+    println!(
+        "samples: {}, loss: {:.6}, accuracy: {:.2}%",
+        x.cols,
+        loss,
+        accuracy * 100.0
+    );
+    Ok(())
+}
+
+fn predict(
+    model: &Path,
+    input: Option<&Path>,
+    output: Option<&Path>,
+    scale: Dtype,
+) -> anyhow::Result<()> {
+    let mut net = load_model(model)?;
+    let (input_size, _) = model_sizes(&net, model)?;
+
+    let reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?,
+        ),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let x = &read_features(reader, input_size)? / scale;
+
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    if x.cols > 0 {
+        for batch in x.split_into_batches(PREDICT_BATCH_SIZE) {
+            let probabilities = net.forward(&batch);
+            for col in 0..probabilities.cols {
+                let row: Vec<String> = (0..probabilities.rows)
+                    .map(|r| probabilities.get(r, col).to_string())
+                    .collect();
+                writeln!(writer, "{}", row.join(","))?;
+            }
+        }
+    }
+    writer.flush()?;
+    log::info!("Predicted {} samples", x.cols);
+    Ok(())
+}
+
+fn search(space: Option<&Path>, paths: &Paths) -> anyhow::Result<()> {
+    let space = match space {
+        Some(path) => read_json(path)?,
+        None => SearchSpace::default(),
+    };
+    log::info!("Starting Grid Search over MNIST training configurations...");
+    run_grid_search(&space, paths)
+}
+
+/// Sets up logging and the thread pool, then runs the command.
+pub fn run(cli: Cli) -> anyhow::Result<()> {
+    if cli.command == Command::Help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let mut logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    if let Some(level) = cli.log_level {
+        logger.filter_level(level);
+    }
+    logger.format_source_path(true).init();
+
+    if let Some(threads) = cli.threads {
+        initialize_rayon_pool(threads)?;
+    }
+    let paths = Paths::from_env(
+        cli.data_root.clone(),
+        cli.output_dir.clone(),
+        cli.settings.as_deref(),
+    )?;
+    log::debug!("Data root: {}", paths.data_root.display());
+    log::debug!("Output directory: {}", paths.output_dir.display());
+    std::fs::create_dir_all(&paths.output_dir)
+        .with_context(|| format!("failed to create {}", paths.output_dir.display()))?;
+
+    match &cli.command {
+        Command::Train { config, experiment } => train(config.as_deref(), *experiment, &paths),
+        Command::Eval {
+            model,
+            data,
+            labels,
+            scale,
+        } => eval(model, data, labels, *scale),
+        Command::Predict {
+            model,
+            input,
+            output,
+            scale,
+        } => predict(model, input.as_deref(), output.as_deref(), *scale),
+        Command::Search { space } => search(space.as_deref(), &paths),
+        Command::Help => unreachable!(),
+    }
+}
@@ -0,0 +1,4 @@
+pub mod args;
+pub mod cli_tests;
+pub mod commands;
+pub mod paths;
\ No newline at end of file
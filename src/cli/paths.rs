@@ -0,0 +1,106 @@
+//! Where datasets are read from and results are written to. Each location is taken from the
+//! first of: command-line flag, environment variable, settings file, default.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+pub const DATA_ROOT_VAR: &str = "NEURAL_NETWORKS_DATA_ROOT";
+pub const OUTPUT_DIR_VAR: &str = "NEURAL_NETWORKS_OUTPUT_DIR";
+/// Settings file read from the working directory if `--settings` is not given.
+pub const DEFAULT_SETTINGS_FILE: &str = "neural-networks.json";
+pub const DEFAULT_DATA_ROOT: &str = "data";
+pub const DEFAULT_OUTPUT_DIR: &str = ".";
+
+/// Contents of the settings file. Relative paths are relative to the file's directory.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    pub data_root: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+}
+
+impl Settings {
+    /// Reads `path`, or `DEFAULT_SETTINGS_FILE` if it exists. No file means no settings.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Settings> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_SETTINGS_FILE).exists() => Path::new(DEFAULT_SETTINGS_FILE),
+            None => return Ok(Settings::default()),
+        };
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read settings from {}", path.display()))?;
+        let settings: Settings = serde_json::from_str(&json)
+            .with_context(|| format!("failed to parse settings in {}", path.display()))?;
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        Ok(Settings {
+            data_root: settings.data_root.map(|p| base.join(p)),
+            output_dir: settings.output_dir.map(|p| base.join(p)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paths {
+    /// Directory containing the dataset CSV files.
+    pub data_root: PathBuf,
+    /// Directory receiving models, checkpoints, plots and results.
+    pub output_dir: PathBuf,
+}
+
+impl Paths {
+    /// Combines the flags, the variables returned by `env` and the settings by priority.
+    pub fn resolve(
+        data_root: Option<PathBuf>,
+        output_dir: Option<PathBuf>,
+        env: impl Fn(&str) -> Option<String>,
+        settings: Settings,
+    ) -> Paths {
+        let pick = |flag: Option<PathBuf>, var, setting: Option<PathBuf>, default| {
+            flag.or_else(|| env(var).filter(|v| !v.is_empty()).map(PathBuf::from))
+                .or(setting)
+                .unwrap_or_else(|| PathBuf::from(default))
+        };
+        Paths {
+            data_root: pick(
+                data_root,
+                DATA_ROOT_VAR,
+                settings.data_root,
+                DEFAULT_DATA_ROOT,
+            ),
+            output_dir: pick(
+                output_dir,
+                OUTPUT_DIR_VAR,
+                settings.output_dir,
+                DEFAULT_OUTPUT_DIR,
+            ),
+        }
+    }
+
+    /// Resolves the paths from the process environment and the settings file.
+    pub fn from_env(
+        data_root: Option<PathBuf>,
+        output_dir: Option<PathBuf>,
+        settings_file: Option<&Path>,
+    ) -> anyhow::Result<Paths> {
+        let settings = Settings::load(settings_file)?;
+        Ok(Paths::resolve(
+            data_root,
+            output_dir,
+            |var| std::env::var(var).ok(),
+            settings,
+        ))
+    }
+
+    /// A dataset file inside the data root.
+    pub fn data(&self, file_name: &str) -> PathBuf {
+        self.data_root.join(file_name)
+    }
+
+    /// Directory for training plots.
+    pub fn plots(&self) -> PathBuf {
+        self.output_dir.join("plots")
+    }
+}
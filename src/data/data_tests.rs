@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::load::read_features;
+
+    #[test]
+    fn test_read_features_one_sample_per_row() {
+        let csv = "1,2,3\n4, 5,6\n";
+        let x = read_features(csv.as_bytes(), 3).unwrap();
+
+        assert_eq!((x.rows, x.cols), (3, 2));
+        assert_eq!(x.get(0, 0), 1.0);
+        assert_eq!(x.get(1, 1), 5.0);
+        assert_eq!(x.get(2, 1), 6.0);
+    }
+
+    #[test]
+    fn test_read_features_rejects_wrong_width() {
+        let error = read_features("1,2\n".as_bytes(), 3).unwrap_err();
+        assert!(error.to_string().contains("column count"));
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::anyhow;
+use anyhow::{Context, anyhow};
 
 use crate::{Dtype, data_structures::matrix::Matrix}; // Assuming Matrix is defined elsewhere
 
@@ -13,7 +13,8 @@ pub fn load_data(
     log::info!("Reading input from: {} and labels from: {}", x_path, y_path);
 
     // --- X Data Loading: Load all records ---
-    let file_x = std::fs::File::open(x_path)?;
+    let file_x =
+        std::fs::File::open(x_path).with_context(|| format!("failed to open {}", x_path))?;
     let mut rdr_x = csv::ReaderBuilder::new()
         .has_headers(false)
         .from_reader(file_x);
@@ -28,7 +29,8 @@ pub fn load_data(
         return Err(anyhow!("No data found in X CSV at path: {}", x_path));
     }
 
-    let file_y = std::fs::File::open(y_path)?;
+    let file_y =
+        std::fs::File::open(y_path).with_context(|| format!("failed to open {}", y_path))?;
     let mut rdr_y = csv::ReaderBuilder::new()
         .has_headers(false)
         .from_reader(file_y);
@@ -76,14 +78,14 @@ pub fn load_data(
         }
 
         for feature_index in 0..input_size {
-            let value: Dtype = (&x_chunk[feature_index])
+            let value: Dtype = x_chunk[feature_index]
                 .parse()
                 .map_err(anyhow::Error::from)?;
             inputs_train.set(feature_index, i, value);
         }
 
         // --- Populate Y Batch Matrix (One-Hot Encoded) ---
-        let class_index: usize = (&y_chunk[0]).parse().map_err(anyhow::Error::from)?;
+        let class_index: usize = y_chunk[0].parse().map_err(anyhow::Error::from)?;
 
         if class_index >= output_size {
             return Err(anyhow!(
@@ -122,16 +124,42 @@ pub fn load_data(
         let valid_i = i - (sample_count - valid_split);
 
         for feature_index in 0..input_size {
-            let value: Dtype = (&x_chunk[feature_index])
+            let value: Dtype = x_chunk[feature_index]
                 .parse()
                 .map_err(anyhow::Error::from)?;
             inputs_valid.set(feature_index, valid_i, value);
         }
 
-        let class_index: usize = (&y_chunk[0]).parse().map_err(anyhow::Error::from)?;
+        let class_index: usize = y_chunk[0].parse().map_err(anyhow::Error::from)?;
 
         labels_valid.set(class_index, valid_i, 1.0);
     }
 
     Ok((inputs_train, labels_train, inputs_valid, labels_valid))
 }
+
+/// Reads unlabeled samples, one CSV row of `input_size` values per sample (no header), as the
+/// columns of an `(input_size, samples)` matrix.
+pub fn read_features<R: std::io::Read>(reader: R, input_size: usize) -> anyhow::Result<Matrix> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(reader);
+    let records: Vec<csv::StringRecord> = rdr.records().collect::<Result<_, _>>()?;
+
+    let mut inputs = Matrix::new(input_size, records.len());
+    for (i, record) in records.iter().enumerate() {
+        if record.len() != input_size {
+            return Err(anyhow!(
+                "row {} has wrong column count: expected {}, got {}",
+                i,
+                input_size,
+                record.len()
+            ));
+        }
+        for (feature_index, field) in record.iter().enumerate() {
+            let value: Dtype = field.trim().parse().map_err(anyhow::Error::from)?;
+            inputs.set(feature_index, i, value);
+        }
+    }
+    Ok(inputs)
+}
@@ -0,0 +1,2 @@
+pub mod data_tests;
+pub mod load;
\ No newline at end of file
@@ -1,10 +1,17 @@
+use alloc::{vec, vec::Vec};
 use core::fmt;
+use core::ops::{Add, Div, Mul, Sub};
+#[cfg(feature = "train")]
 use rand::{Rng, SeedableRng, rng, rngs::StdRng, seq::SliceRandom};
-use std::ops::{Add, Div, Mul, Sub};
+#[cfg(feature = "train")]
+use serde::{Deserialize, Serialize};
 
-use crate::{Dtype, SEED};
+use crate::Dtype;
+#[cfg(feature = "train")]
+use crate::SEED;
 
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "train", derive(Serialize, Deserialize))]
 pub struct Matrix {
     pub rows: usize,
     pub cols: usize,
@@ -55,6 +62,7 @@ impl Matrix {
     /// Creates a new matrix with random values initialized using the Kaiming/He
     /// initialization scale for ReLU (or a similar common initialization).
     /// Uses the default thread-local RNG (non-seedable).
+    #[cfg(feature = "train")]
     pub fn new_random(rows: usize, cols: usize) -> Matrix {
         let seed = rng().random::<u64>();
         Matrix::new_seeded_random(rows, cols, seed)
@@ -62,6 +70,7 @@ impl Matrix {
 
     /// Creates a new matrix with random values using a fixed seed.
     /// This is essential for reproducible training runs.
+    #[cfg(feature = "train")]
     pub fn new_seeded_random(rows: usize, cols: usize, seed: u64) -> Matrix {
         // Pcg64 is a good, fast, and deterministic RNG for seeded use.
 
@@ -105,6 +114,7 @@ impl Matrix {
         result
     }
 
+    #[cfg(feature = "train")]
     pub fn generate_shuffled_indices(&self) -> Vec<usize> {
         let mut rng = StdRng::seed_from_u64(SEED);
         let mut indices: Vec<usize> = (0..self.cols).collect();
@@ -112,7 +122,7 @@ impl Matrix {
         indices
     }
 
-    pub fn shuffle_columns(&mut self, indices: &Vec<usize>) {
+    pub fn shuffle_columns(&mut self, indices: &[usize]) {
         assert_eq!(
             indices.len(),
             self.cols,
@@ -168,6 +178,7 @@ impl Matrix {
         result
     }
 
+    #[cfg(feature = "train")]
     pub fn element_wise_sqrt(&self) -> Matrix {
         let mut result = Matrix::new(self.rows, self.cols);
         for i in 0..self.data.len() {
@@ -90,6 +90,7 @@ mod tests {
         assert_eq!(m.get(1, 2), 4.0);
     }
 
+    #[cfg(feature = "train")]
     #[test]
     fn test_generate_shuffled_indices() {
         let m = Matrix::new(1, 5);
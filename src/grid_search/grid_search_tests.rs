@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use neural_networks::Dtype;
+
+    use crate::grid_search::train_config::TrainConfig;
+
+    #[test]
+    fn test_train_config_reads_legacy_hidden_size_keys() {
+        let best: TrainConfig =
+            serde_json::from_str(include_str!("../../best_train_config.json")).unwrap();
+        assert_eq!(best.hidden_sizes, vec![128, 64, 32]);
+
+        let results: Vec<(TrainConfig, Dtype)> =
+            serde_json::from_str(include_str!("../../all_train_results.json")).unwrap();
+        assert!(
+            results
+                .iter()
+                .all(|(config, _)| config.hidden_sizes.len() == 3)
+        );
+
+        // Written back with `hidden_sizes`, which reads the same.
+        let json = serde_json::to_string(&best).unwrap();
+        assert!(json.contains(r#""hidden_sizes":[128,64,32]"#));
+        let again: TrainConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(again.hidden_sizes, best.hidden_sizes);
+
+        let both = json.replace(r#""learning_rate""#, r#""hidden_size":8,"learning_rate""#);
+        assert!(serde_json::from_str::<TrainConfig>(&both).is_err());
+    }
+}
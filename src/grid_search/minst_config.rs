@@ -1,75 +1,109 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use neural_networks::{
+    Dtype, data::load::load_data, networks::network::Network, networks::spec::ModelSpec,
+};
+
 use crate::{
-    Dtype, grid_search::train_config::TrainConfig, layers::{dense::DenseLayer, relu::ReLULayer, softmax::Softmax}, networks::network::Network, testing::test_net::test_network, training::data_load::load_data
+    cli::paths::Paths,
+    grid_search::train_config::TrainConfig,
+    testing::test_net::test_network,
+    training::{TRAIN_LABELS, TRAIN_VECTORS},
 };
 
-pub fn train_mnist_with_config(config: &TrainConfig) -> anyhow::Result<Dtype> {
+/// Directory (inside the output directory) holding one checkpoint per configuration, so that an
+/// interrupted grid search continues each run from its last completed epoch.
+pub const CHECKPOINT_DIR: &str = "grid_search_checkpoints";
+
+// --- Fixed network I/O sizes ---
+pub const INPUT_SIZE: usize = 784;
+pub const OUTPUT_SIZE: usize = 10;
+
+/// Trains a Fashion MNIST network described by `config`, resuming from its checkpoint in
+/// `checkpoint_dir` if there is one. Returns the network and its test accuracy.
+pub fn train_mnist_with_config(
+    config: &TrainConfig,
+    paths: &Paths,
+    checkpoint_dir: &Path,
+) -> anyhow::Result<(Network, Dtype)> {
     log::info!("===== Running Config =====");
     log::info!("{:?}", config);
     log::info!("==========================");
 
-    // --- Fixed network I/O sizes ---
-    const INPUT_SIZE: usize = 784;
-    const OUTPUT_SIZE: usize = 10;
-
-    // --- Extract config hyperparameters ---
-    let h1 = config.hidden_size;
-    let h2 = config.hidden_size_2;
-    let h3 = config.hidden_size_3;
-    let epochs = config.epochs;
-    let lr = config.learning_rate;
-    let bs = config.batch_size;
-    let momentum = config.momentum;
-    let weight_decay = config.weight_decay;
-    let validation_split = 0.2;
+    std::fs::create_dir_all(checkpoint_dir)?;
+    let checkpoint_path = checkpoint_dir.join(config.checkpoint_name());
+    train_mnist_with_spec(
+        &config.to_model_spec(INPUT_SIZE, OUTPUT_SIZE),
+        paths,
+        Some(&checkpoint_path),
+    )
+}
+
+/// Trains the network described by `spec` on Fashion MNIST, checkpointing to (and resuming
+/// from) `checkpoint_path` if given. Returns the network and its test accuracy.
+pub fn train_mnist_with_spec(
+    spec: &ModelSpec,
+    paths: &Paths,
+    checkpoint_path: Option<&Path>,
+) -> anyhow::Result<(Network, Dtype)> {
+    if (spec.input_size, spec.output_size()) != (INPUT_SIZE, Some(OUTPUT_SIZE)) {
+        return Err(anyhow!(
+            "Fashion MNIST needs {} inputs and {} outputs, the model spec has {} and {:?}",
+            INPUT_SIZE,
+            OUTPUT_SIZE,
+            spec.input_size,
+            spec.output_size()
+        ));
+    }
+    let training = &spec.training;
 
     // --- Load data ---
-    let path_inputs =
-        std::fs::canonicalize("/home/xhatalc/pv021_project/data/fashion_mnist_train_vectors.csv")?;
-    let path_labels =
-        std::fs::canonicalize("/home/xhatalc/pv021_project/data/fashion_mnist_train_labels.csv")?;
+    let path_inputs = paths.data(TRAIN_VECTORS);
+    let path_labels = paths.data(TRAIN_LABELS);
 
     let (mut x_train, y_train, mut x_valid, y_valid) = load_data(
-        path_inputs.to_str().unwrap(),
-        path_labels.to_str().unwrap(),
+        &path_inputs.to_string_lossy(),
+        &path_labels.to_string_lossy(),
         INPUT_SIZE,
         OUTPUT_SIZE,
-        validation_split,
+        training.validation_split,
     )?;
 
     x_train = &x_train / 255.0; // Normalize to [0, 1]
-    x_valid = &x_valid / 255.0; // Normalize to [0, 1]
+    x_valid = &x_valid / 255.0;
 
     log::info!("Dataset size: {} samples", x_train.cols);
 
-    // --- Build network as defined by config ---
-    let config = crate::layers::dense::ConfigDenseLayer {
-        learning_rate: lr,
-        momentum_factor: momentum,
-        weight_decay,
-    };
-
-    let mut net = Network::new();
-    net.add_layer(DenseLayer::new(INPUT_SIZE, h1, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(h1, h2, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(h2, h3, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(h3, OUTPUT_SIZE, &config));
-    net.add_layer(Softmax::new());
+    // --- Build network as defined by the spec ---
+    let mut net = spec.build()?;
 
     // For grid search, disable plots.
     // Enable manually when doing single-run training.
-    // net.add_callback(PlottingCallback::new("./fashion_minst.png"));
+    // net.add_callback(PlottingCallback::new(paths.plots(), "fashion_minst"));
 
-    log::info!("Training for {} epochs…", epochs);
+    if let Some(checkpoint_path) = checkpoint_path {
+        if checkpoint_path.exists() {
+            net.load_checkpoint(checkpoint_path)?;
+        }
+        net.set_checkpoint_path(checkpoint_path);
+    }
 
-    net.train(&x_train, &y_train, epochs, bs)?;
+    log::info!("Training for {} epochs…", training.epochs);
+
+    // Continues a loaded checkpoint, otherwise trains from the first epoch.
+    net.resume_training(
+        &x_train,
+        &y_train,
+        Some((&x_valid, &y_valid)),
+        training.epochs,
+        training.batch_size,
+    )?;
 
     // --- Final evaluation on the full dataset ---
-    let (_final_loss, accuracy) = test_network(&mut net, INPUT_SIZE, OUTPUT_SIZE)?;
+    let (_final_loss, accuracy) = test_network(&mut net, paths, INPUT_SIZE, OUTPUT_SIZE)?;
 
     log::info!("Final accuracy: {:.4}", accuracy);
 
-    Ok(accuracy)
+    Ok((net, accuracy))
 }
@@ -1,2 +1,3 @@
+pub mod grid_search_tests;
 pub mod minst_config;
-pub mod train_config;
+pub mod train_config;
\ No newline at end of file
@@ -1,13 +1,22 @@
+use anyhow::anyhow;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 
-use crate::{Dtype, grid_search::minst_config::train_mnist_with_config};
+use neural_networks::{
+    Dtype,
+    networks::spec::{ModelSpec, OptimizerSpec, TrainingSpec},
+};
+
+use crate::{
+    cli::paths::Paths,
+    grid_search::minst_config::{CHECKPOINT_DIR, train_mnist_with_config},
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "TrainConfigFields")]
 pub struct TrainConfig {
-    pub hidden_size: usize,
-    pub hidden_size_2: usize,
-    pub hidden_size_3: usize,
+    /// Sizes of the hidden ReLU layers, any number of them.
+    pub hidden_sizes: Vec<usize>,
     pub learning_rate: Dtype,
     pub batch_size: usize,
     pub momentum: Dtype,
@@ -15,42 +24,149 @@ pub struct TrainConfig {
     pub epochs: usize,
 }
 
-pub fn run_grid_search() -> anyhow::Result<()> {
-    let learning_rates = vec![0.001, 0.01, 0.1];
-    let batch_sizes = vec![64, 128];
-    let hidden_sizes = vec![128, 64];
-    let hidden_sizes_2 = vec![64, 32];
-    let hidden_sizes_3 = vec![32, 16];
-    let weight_decays = vec![0.0, 0.00001];
-    let momenta = vec![0.1, 0.001, 0.9];
-    let epochs = 30;
-
-    // create config list
-    let mut configs = Vec::new();
-    for &lr in &learning_rates {
-        for &bs in &batch_sizes {
-            for &hs in &hidden_sizes {
-                for &wd in &weight_decays {
-                    for &mom in &momenta {
-                        for &hs2 in &hidden_sizes_2 {
-                            for &hs3 in &hidden_sizes_3 {
-                                configs.push(TrainConfig {
-                                    learning_rate: lr,
-                                    batch_size: bs,
-                                    hidden_size: hs,
-                                    hidden_size_2: hs2,
-                                    hidden_size_3: hs3,
-                                    weight_decay: wd,
-                                    momentum: mom,
-                                    epochs,
-                                });
-                            }
+/// A serialized `TrainConfig`. Results written before `hidden_sizes` store up to three
+/// hidden layers as `hidden_size`, `hidden_size_2` and `hidden_size_3`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrainConfigFields {
+    hidden_sizes: Option<Vec<usize>>,
+    hidden_size: Option<usize>,
+    hidden_size_2: Option<usize>,
+    hidden_size_3: Option<usize>,
+    learning_rate: Dtype,
+    batch_size: usize,
+    momentum: Dtype,
+    weight_decay: Dtype,
+    epochs: usize,
+}
+
+impl TryFrom<TrainConfigFields> for TrainConfig {
+    type Error = String;
+
+    fn try_from(fields: TrainConfigFields) -> Result<Self, Self::Error> {
+        let legacy: Vec<usize> = [
+            fields.hidden_size,
+            fields.hidden_size_2,
+            fields.hidden_size_3,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let hidden_sizes = match (fields.hidden_sizes, legacy.is_empty()) {
+            (Some(hidden_sizes), true) => hidden_sizes,
+            (None, false) => legacy,
+            (Some(_), false) => {
+                return Err("`hidden_sizes` cannot be combined with `hidden_size` keys".into());
+            }
+            (None, true) => return Err("missing field `hidden_sizes`".into()),
+        };
+        Ok(TrainConfig {
+            hidden_sizes,
+            learning_rate: fields.learning_rate,
+            batch_size: fields.batch_size,
+            momentum: fields.momentum,
+            weight_decay: fields.weight_decay,
+            epochs: fields.epochs,
+        })
+    }
+}
+
+impl TrainConfig {
+    /// File name identifying this configuration, used for its training checkpoint.
+    pub fn checkpoint_name(&self) -> String {
+        let hidden: Vec<String> = self.hidden_sizes.iter().map(|h| h.to_string()).collect();
+        format!(
+            "h{}_lr{}_bs{}_m{}_wd{}.ckpt.json",
+            hidden.join("-"),
+            self.learning_rate,
+            self.batch_size,
+            self.momentum,
+            self.weight_decay
+        )
+    }
+
+    /// The MLP trained for this configuration, with an `Adam` optimizer.
+    pub fn to_model_spec(&self, input_size: usize, output_size: usize) -> ModelSpec {
+        ModelSpec::mlp(input_size, &self.hidden_sizes, output_size)
+            .with_optimizer(OptimizerSpec::adam(self.learning_rate, self.weight_decay))
+            .with_training(TrainingSpec {
+                epochs: self.epochs,
+                batch_size: self.batch_size,
+                ..TrainingSpec::default()
+            })
+    }
+}
+
+/// Hyperparameter values to combine in a grid search. Missing fields keep their defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchSpace {
+    pub learning_rates: Vec<Dtype>,
+    pub batch_sizes: Vec<usize>,
+    /// Hidden layer sizes of each architecture to try.
+    pub hidden_sizes: Vec<Vec<usize>>,
+    pub weight_decays: Vec<Dtype>,
+    pub momenta: Vec<Dtype>,
+    pub epochs: usize,
+}
+
+impl Default for SearchSpace {
+    fn default() -> Self {
+        SearchSpace {
+            learning_rates: vec![0.001, 0.01, 0.1],
+            batch_sizes: vec![64, 128],
+            hidden_sizes: vec![
+                vec![128, 64, 32],
+                vec![128, 64, 16],
+                vec![128, 32, 32],
+                vec![128, 32, 16],
+                vec![64, 64, 32],
+                vec![64, 64, 16],
+                vec![64, 32, 32],
+                vec![64, 32, 16],
+            ],
+            weight_decays: vec![0.0, 0.00001],
+            momenta: vec![0.1, 0.001, 0.9],
+            epochs: 30,
+        }
+    }
+}
+
+impl SearchSpace {
+    /// Every combination of the values, one `TrainConfig` each.
+    pub fn configs(&self) -> Vec<TrainConfig> {
+        let mut configs = Vec::new();
+        for &lr in &self.learning_rates {
+            for &bs in &self.batch_sizes {
+                for hidden_sizes in &self.hidden_sizes {
+                    for &wd in &self.weight_decays {
+                        for &mom in &self.momenta {
+                            configs.push(TrainConfig {
+                                hidden_sizes: hidden_sizes.clone(),
+                                learning_rate: lr,
+                                batch_size: bs,
+                                momentum: mom,
+                                weight_decay: wd,
+                                epochs: self.epochs,
+                            });
                         }
                     }
                 }
             }
         }
+        configs
+    }
+}
+
+/// Trains every configuration of `space` in parallel and writes the best one and all results
+/// to the output directory.
+pub fn run_grid_search(space: &SearchSpace, paths: &Paths) -> anyhow::Result<()> {
+    let configs = space.configs();
+    if configs.is_empty() {
+        return Err(anyhow!("the search space has no configurations"));
     }
+    let output_dir = paths.output_dir.as_path();
+    let checkpoint_dir = output_dir.join(CHECKPOINT_DIR);
 
     log::info!("Running {} configurations in parallel…", configs.len());
 
@@ -58,9 +174,9 @@ pub fn run_grid_search() -> anyhow::Result<()> {
     let results: Vec<_> = configs
         .par_iter()
         .map(|cfg| {
-            let res = train_mnist_with_config(cfg);
+            let res = train_mnist_with_config(cfg, paths, &checkpoint_dir);
             match res {
-                Ok(acc) => (cfg.clone(), acc),
+                Ok((_, acc)) => (cfg.clone(), acc),
                 Err(e) => {
                     log::error!("Config {:?} failed: {:?}", cfg, e);
                     (cfg.clone(), -1.0)
@@ -85,9 +201,9 @@ pub fn run_grid_search() -> anyhow::Result<()> {
 
     // save best config to file
     let best_cfg_json = serde_json::to_string_pretty(&best_cfg)?;
-    std::fs::write("best_train_config.json", best_cfg_json)?;
+    std::fs::write(output_dir.join("best_train_config.json"), best_cfg_json)?;
     // save all configs and results to file in descending order
     let all_results_json = serde_json::to_string_pretty(&results)?;
-    std::fs::write("all_train_results.json", all_results_json)?;
+    std::fs::write(output_dir.join("all_train_results.json"), all_results_json)?;
     Ok(())
 }
@@ -0,0 +1,110 @@
+use anyhow::anyhow;
+
+use crate::{Dtype, data_structures::matrix::Matrix};
+
+/// An n-dimensional array read from an external format, with row-major (C order) `data`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NdArray {
+    pub shape: Vec<usize>,
+    pub data: Vec<Dtype>,
+}
+
+/// How a 2-D weight array is oriented relative to the `(output_size, input_size)` convention
+/// of `DenseLayer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightLayout {
+    /// `[output_size, input_size]`, as in PyTorch `nn.Linear` and ONNX `Gemm` with `transB = 1`.
+    OutputInput,
+    /// `[input_size, output_size]`, as in Keras `Dense` kernels and ONNX `MatMul`.
+    InputOutput,
+}
+
+impl NdArray {
+    /// The number of values in an array of `shape`, or an error if it overflows `usize`.
+    pub fn element_count(shape: &[usize]) -> anyhow::Result<usize> {
+        shape
+            .iter()
+            .try_fold(1usize, |count, &dim| count.checked_mul(dim))
+            .ok_or_else(|| anyhow!("array shape {:?} is too large", shape))
+    }
+
+    pub fn new(shape: Vec<usize>, data: Vec<Dtype>) -> anyhow::Result<NdArray> {
+        let expected = NdArray::element_count(&shape)?;
+        if data.len() != expected {
+            return Err(anyhow!(
+                "array of shape {:?} needs {} values, got {}",
+                shape,
+                expected,
+                data.len()
+            ));
+        }
+        Ok(NdArray { shape, data })
+    }
+
+    /// Builds a row-major array from column-major (Fortran order) data.
+    pub fn from_fortran_order(shape: Vec<usize>, data: Vec<Dtype>) -> anyhow::Result<NdArray> {
+        // Column-major data of `shape` is row-major data of the reversed shape.
+        let reversed = NdArray::new(shape.iter().rev().copied().collect(), data)?;
+        Ok(reversed.reverse_axes())
+    }
+
+    /// Transposes all axes, e.g. a regular matrix transpose for 2-D arrays.
+    pub fn reverse_axes(&self) -> NdArray {
+        let shape: Vec<usize> = self.shape.iter().rev().copied().collect();
+        let mut data = vec![0.0; self.data.len()];
+        let mut index = vec![0; shape.len()];
+        for value in data.iter_mut() {
+            // `index` walks the new shape in row-major order; read the source at the reversed index.
+            let source: usize = index
+                .iter()
+                .enumerate()
+                .map(|(axis, i)| i * self.stride(self.shape.len() - 1 - axis))
+                .sum();
+            *value = self.data[source];
+            for axis in (0..shape.len()).rev() {
+                index[axis] += 1;
+                if index[axis] < shape[axis] {
+                    break;
+                }
+                index[axis] = 0;
+            }
+        }
+        NdArray { shape, data }
+    }
+
+    fn stride(&self, axis: usize) -> usize {
+        self.shape[axis + 1..].iter().product()
+    }
+
+    /// Converts a 2-D weight array to a `(output_size, input_size)` matrix.
+    pub fn to_weights(&self, layout: WeightLayout) -> anyhow::Result<Matrix> {
+        let [rows, cols] = self.shape[..] else {
+            return Err(anyhow!("weights must be 2-D, got shape {:?}", self.shape));
+        };
+        // Row-major [rows, cols] is column-major (cols, rows), i.e. the transpose as a `Matrix`.
+        let transposed = Matrix {
+            rows: cols,
+            cols: rows,
+            data: self.data.clone(),
+        };
+        Ok(match layout {
+            WeightLayout::OutputInput => transposed.transpose(),
+            WeightLayout::InputOutput => transposed,
+        })
+    }
+
+    /// Converts a bias vector of shape `[n]`, `[n, 1]` or `[1, n]` to an `(n, 1)` matrix.
+    pub fn to_biases(&self) -> anyhow::Result<Matrix> {
+        match self.shape[..] {
+            [_] | [_, 1] | [1, _] => Ok(Matrix {
+                rows: self.data.len(),
+                cols: 1,
+                data: self.data.clone(),
+            }),
+            _ => Err(anyhow!(
+                "biases must be a vector, got shape {:?}",
+                self.shape
+            )),
+        }
+    }
+}
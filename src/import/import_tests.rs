@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        data_structures::matrix::Matrix,
+        import::{
+            array::{NdArray, WeightLayout},
+            npy::{NPY_MAGIC, parse_npy, to_npy_bytes},
+            npz::{parse_npz, to_npz_bytes},
+            onnx::dense_parameters,
+        },
+        layers::{
+            dense::{ConfigDenseLayer, DenseLayer},
+            relu::ReLULayer,
+            softmax::Softmax,
+        },
+        networks::network::Network,
+        onnx::reader::{OnnxModel, OnnxNode, OnnxTensor},
+    };
+
+    fn net(input_size: usize, hidden: usize, output_size: usize) -> Network {
+        let config = ConfigDenseLayer::default();
+        let mut net = Network::new();
+        net.add_layer(DenseLayer::new(input_size, hidden, &config));
+        net.add_layer(ReLULayer::new());
+        net.add_layer(DenseLayer::new(hidden, output_size, &config));
+        net.add_layer(Softmax::new());
+        net
+    }
+
+    // np.arange(6, dtype=np.float32).reshape(2, 3)
+    fn arange_2x3() -> NdArray {
+        NdArray::new(vec![2, 3], (0..6).map(|v| v as f32).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_npy_round_trip() {
+        let array = arange_2x3();
+        let bytes = to_npy_bytes(&array);
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        assert_eq!(parse_npy(&bytes).unwrap(), array);
+    }
+
+    #[test]
+    fn test_npy_fortran_order_and_float64() {
+        // np.asfortranarray(np.arange(6.0).reshape(2, 3)) stores the columns one after another.
+        let header = "{'descr': '<f8', 'fortran_order': True, 'shape': (2, 3), }\n";
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for v in [0.0f64, 3.0, 1.0, 4.0, 2.0, 5.0] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        assert_eq!(parse_npy(&bytes).unwrap(), arange_2x3());
+
+        let truncated = &bytes[..bytes.len() - 8];
+        assert!(parse_npy(truncated).is_err());
+    }
+
+    #[test]
+    fn test_npy_rejects_overflowing_shapes() {
+        // The element count overflows, then the byte count of 2^61 float64 values does.
+        for (descr, shape) in [
+            ("<f4", "(4611686018427387904, 4)"),
+            ("<f8", "(1152921504606846976, 2)"),
+        ] {
+            let header = format!(
+                "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}\n",
+                descr, shape
+            );
+            let mut bytes = NPY_MAGIC.to_vec();
+            bytes.extend_from_slice(&[1, 0]);
+            bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(header.as_bytes());
+            let err = parse_npy(&bytes).err().unwrap();
+            assert!(format!("{:#}", err).contains("too large"), "{}", shape);
+        }
+
+        assert!(NdArray::new(vec![usize::MAX, 2], Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_weight_layouts() {
+        // [out = 2, in = 3] and its transpose [in = 3, out = 2] give the same matrix.
+        let output_input = arange_2x3().to_weights(WeightLayout::OutputInput).unwrap();
+        let input_output = arange_2x3()
+            .reverse_axes()
+            .to_weights(WeightLayout::InputOutput)
+            .unwrap();
+        assert_eq!((output_input.rows, output_input.cols), (2, 3));
+        assert_eq!(output_input.get(1, 2), 5.0);
+        assert_eq!(output_input.data, input_output.data);
+    }
+
+    #[test]
+    fn test_npz_round_trip() {
+        let a = arange_2x3();
+        let b = NdArray::new(vec![3], vec![1.0, 2.0, 3.0]).unwrap();
+        for compressed in [false, true] {
+            let bytes = to_npz_bytes(&[("a", &a), ("b", &b)], compressed).unwrap();
+            let arrays = parse_npz(&bytes).unwrap();
+            assert_eq!(arrays.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+            assert_eq!(arrays["a"], a);
+            assert_eq!(arrays["b"], b);
+        }
+    }
+
+    #[test]
+    fn test_import_npz_keras_layout() {
+        let kernel_0 = NdArray::new(vec![4, 3], (0..12).map(|v| v as f32).collect()).unwrap();
+        let bias_0 = NdArray::new(vec![3], vec![0.5; 3]).unwrap();
+        let kernel_1 = NdArray::new(vec![3, 2], vec![1.0; 6]).unwrap();
+        let bias_1 = NdArray::new(vec![2], vec![0.0; 2]).unwrap();
+        let bytes = to_npz_bytes(
+            &[
+                ("kernel_0", &kernel_0),
+                ("bias_0", &bias_0),
+                ("kernel_1", &kernel_1),
+                ("bias_1", &bias_1),
+            ],
+            false,
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join("neural_networks_import.npz");
+        std::fs::write(&path, bytes).unwrap();
+
+        let names = [("kernel_0", "bias_0"), ("kernel_1", "bias_1")];
+        let mut wrong = net(4, 3, 2);
+        let err = wrong.import_npz_weights(&path, &names, WeightLayout::OutputInput);
+        let mut net = net(4, 3, 2);
+        let result = net.import_npz_weights(&path, &names, WeightLayout::InputOutput);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(format!("{:#}", err.err().unwrap()).contains("expects weights of shape"));
+        result.unwrap();
+        // Keras kernel[i][o] is weights(o, i).
+        let weights = net.layers[0].get_weights().unwrap();
+        assert_eq!(weights.get(2, 1), 1.0 * 3.0 + 2.0);
+        assert_eq!(net.layers[0].get_biases().unwrap().data, vec![0.5; 3]);
+    }
+
+    #[test]
+    fn test_import_onnx_weights() {
+        let mut source = net(4, 5, 3);
+        for parameter in source.layers[2].parameters_mut() {
+            parameter.value.data.iter_mut().for_each(|v| *v *= -2.0);
+        }
+        let path = std::env::temp_dir().join("neural_networks_import.onnx");
+        source.export_onnx(&path).unwrap();
+
+        let mut target = net(4, 5, 3);
+        let imported = target.import_onnx_weights(&path);
+        let mut too_small = net(4, 6, 3);
+        let mismatch = too_small.import_onnx_weights(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        imported.unwrap();
+        assert!(mismatch.is_err());
+        let x = Matrix::new_seeded_random(4, 3, 9);
+        assert_eq!(source.forward(&x).data, target.forward(&x).data);
+    }
+
+    #[test]
+    fn test_onnx_matmul_bias_in_either_add_input() {
+        let node = |op_type: &str, inputs: [&str; 2], output: &str| OnnxNode {
+            name: output.to_string(),
+            op_type: op_type.to_string(),
+            inputs: inputs.iter().map(|name| name.to_string()).collect(),
+            outputs: vec![output.to_string()],
+            attributes: Vec::new(),
+        };
+        let tensor = |name: &str, dims: Vec<usize>, data: Vec<f32>| OnnxTensor {
+            name: name.to_string(),
+            dims,
+            data,
+        };
+        let model = OnnxModel {
+            ir_version: 8,
+            opset_version: None,
+            producer_name: String::new(),
+            nodes: vec![
+                node("MatMul", ["x", "w0"], "h0"),
+                node("Add", ["h0", "b0"], "y0"),
+                node("MatMul", ["y0", "w1"], "h1"),
+                node("Add", ["b1", "h1"], "y1"),
+            ],
+            initializers: vec![
+                tensor("w0", vec![2, 2], vec![1.0, 0.0, 0.0, 1.0]),
+                tensor("b0", vec![2], vec![0.5, -0.5]),
+                tensor("w1", vec![2, 1], vec![1.0, 1.0]),
+                tensor("b1", vec![1], vec![2.0]),
+            ],
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        };
+
+        let biases = dense_parameters(&model)
+            .unwrap()
+            .into_iter()
+            .map(|(_, biases)| biases.data)
+            .collect::<Vec<_>>();
+        assert_eq!(biases, vec![vec![0.5, -0.5], vec![2.0]]);
+    }
+}
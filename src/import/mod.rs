@@ -0,0 +1,6 @@
+pub mod array;
+pub mod import_tests;
+pub mod network_import;
+pub mod npy;
+pub mod npz;
+pub mod onnx;
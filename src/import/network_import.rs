@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+
+use crate::{
+    data_structures::matrix::Matrix,
+    import::{array::WeightLayout, npy::read_npy, npz::read_npz, onnx::dense_parameters},
+    networks::network::Network,
+    onnx::reader::OnnxModel,
+};
+
+impl Network {
+    /// Overwrites the parameters of the dense layers, in order, with `(weights, biases)` pairs
+    /// of shape `(output_size, input_size)` and `(output_size, 1)`. Nothing is changed unless
+    /// every pair matches its layer.
+    pub fn import_dense_parameters(
+        &mut self,
+        parameters: Vec<(Matrix, Matrix)>,
+    ) -> anyhow::Result<()> {
+        let dense_layers: Vec<usize> = (0..self.layers.len())
+            .filter(|&i| self.layers[i].get_weights().is_some())
+            .collect();
+        if dense_layers.len() != parameters.len() {
+            return Err(anyhow!(
+                "the network has {} dense layers, got parameters for {}",
+                dense_layers.len(),
+                parameters.len()
+            ));
+        }
+
+        for (&i, (weights, biases)) in dense_layers.iter().zip(parameters.iter()) {
+            let expected = self.layers[i].get_weights().unwrap();
+            if (weights.rows, weights.cols) != (expected.rows, expected.cols) {
+                return Err(anyhow!(
+                    "layer {} expects weights of shape (output_size, input_size) = ({}, {}), got ({}, {}); is the weight layout right?",
+                    i,
+                    expected.rows,
+                    expected.cols,
+                    weights.rows,
+                    weights.cols
+                ));
+            }
+            if (biases.rows, biases.cols) != (expected.rows, 1) {
+                return Err(anyhow!(
+                    "layer {} expects {} biases, got shape ({}, {})",
+                    i,
+                    expected.rows,
+                    biases.rows,
+                    biases.cols
+                ));
+            }
+        }
+
+        for (i, (weights, biases)) in dense_layers.into_iter().zip(parameters) {
+            let mut layer_parameters = self.layers[i].parameters_mut();
+            *layer_parameters[0].value = weights;
+            *layer_parameters[1].value = biases;
+        }
+        Ok(())
+    }
+
+    /// Initializes the dense layers from `.npy` files, one `(weights, biases)` pair per layer.
+    pub fn import_npy_weights<P: AsRef<Path>>(
+        &mut self,
+        files: &[(P, P)],
+        layout: WeightLayout,
+    ) -> anyhow::Result<()> {
+        let parameters = files
+            .iter()
+            .map(|(weights, biases)| {
+                Ok((
+                    read_npy(weights)?.to_weights(layout)?,
+                    read_npy(biases)?.to_biases()?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.import_dense_parameters(parameters)
+    }
+
+    /// Initializes the dense layers from arrays of an `.npz` archive, given the names of the
+    /// `(weights, biases)` arrays of every layer.
+    pub fn import_npz_weights<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        names: &[(&str, &str)],
+        layout: WeightLayout,
+    ) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let arrays = read_npz(path)?;
+        let array = |name: &str| {
+            arrays
+                .get(name)
+                .ok_or_else(|| anyhow!("{} has no array '{}'", path.display(), name))
+        };
+        let parameters = names
+            .iter()
+            .map(|(weights, biases)| {
+                Ok((
+                    array(weights)?
+                        .to_weights(layout)
+                        .with_context(|| format!("invalid array '{}'", weights))?,
+                    array(biases)?
+                        .to_biases()
+                        .with_context(|| format!("invalid array '{}'", biases))?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.import_dense_parameters(parameters)
+    }
+
+    /// Initializes the dense layers from the initializers of an ONNX MLP, see `dense_parameters`.
+    pub fn import_onnx_weights<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let model = OnnxModel::read(path)?;
+        let parameters = dense_parameters(&model)
+            .with_context(|| format!("failed to import weights from {}", path.display()))?;
+        self.import_dense_parameters(parameters)
+    }
+}
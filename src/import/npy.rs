@@ -0,0 +1,145 @@
+//! Reader and writer for NumPy `.npy` files (format versions 1.0 to 3.0) holding
+//! little- or big-endian `float32`/`float64` arrays.
+
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+
+use crate::{Dtype, import::array::NdArray};
+
+pub const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Extracts the value following `'key':` from the header dictionary.
+fn header_value<'a>(header: &'a str, key: &str) -> anyhow::Result<&'a str> {
+    let pattern = format!("'{}':", key);
+    let start = header
+        .find(&pattern)
+        .ok_or_else(|| anyhow!("npy header has no '{}' entry", key))?
+        + pattern.len();
+    Ok(header[start..].trim_start())
+}
+
+fn parse_shape(value: &str) -> anyhow::Result<Vec<usize>> {
+    let inner = value
+        .strip_prefix('(')
+        .and_then(|rest| rest.split(')').next())
+        .ok_or_else(|| anyhow!("malformed shape in npy header"))?;
+    inner
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| {
+            dim.parse::<usize>()
+                .map_err(|_| anyhow!("invalid dimension '{}' in npy header", dim))
+        })
+        .collect()
+}
+
+/// Parses the contents of a `.npy` file.
+pub fn parse_npy(bytes: &[u8]) -> anyhow::Result<NdArray> {
+    if !bytes.starts_with(NPY_MAGIC) || bytes.len() < 10 {
+        return Err(anyhow!("not a .npy file"));
+    }
+    let major = bytes[6];
+    let (header_len, header_start) = match major {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 => {
+            let len = bytes
+                .get(8..12)
+                .ok_or_else(|| anyhow!("truncated npy header"))?;
+            (u32::from_le_bytes(len.try_into().unwrap()) as usize, 12)
+        }
+        other => return Err(anyhow!("unsupported npy format version {}", other)),
+    };
+    let header = bytes
+        .get(header_start..header_start + header_len)
+        .ok_or_else(|| anyhow!("truncated npy header"))?;
+    let header = std::str::from_utf8(header).context("npy header is not valid text")?;
+    let payload = &bytes[header_start + header_len..];
+
+    let descr = header_value(header, "descr")?;
+    let descr = descr
+        .strip_prefix('\'')
+        .and_then(|rest| rest.split('\'').next())
+        .ok_or_else(|| anyhow!("malformed descr in npy header"))?;
+    let fortran_order = header_value(header, "fortran_order")?.starts_with("True");
+    let shape = parse_shape(header_value(header, "shape")?)?;
+
+    let count = NdArray::element_count(&shape)?;
+    let (size, decode): (usize, fn(&[u8]) -> Dtype) = match descr {
+        "<f4" => (4, |b| f32::from_le_bytes(b.try_into().unwrap()) as Dtype),
+        ">f4" => (4, |b| f32::from_be_bytes(b.try_into().unwrap()) as Dtype),
+        "<f8" => (8, |b| f64::from_le_bytes(b.try_into().unwrap()) as Dtype),
+        ">f8" => (8, |b| f64::from_be_bytes(b.try_into().unwrap()) as Dtype),
+        other => {
+            return Err(anyhow!(
+                "unsupported npy dtype '{}', only float32 and float64 are supported",
+                other
+            ));
+        }
+    };
+    let expected = count
+        .checked_mul(size)
+        .ok_or_else(|| anyhow!("npy shape {:?} of '{}' is too large", shape, descr))?;
+    if payload.len() != expected {
+        return Err(anyhow!(
+            "npy data has {} bytes, shape {:?} of '{}' needs {}",
+            payload.len(),
+            shape,
+            descr,
+            expected
+        ));
+    }
+    let data = payload.chunks_exact(size).map(decode).collect();
+
+    if fortran_order {
+        NdArray::from_fortran_order(shape, data)
+    } else {
+        NdArray::new(shape, data)
+    }
+}
+
+pub fn read_npy<P: AsRef<Path>>(path: P) -> anyhow::Result<NdArray> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_npy(&bytes).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Encodes an array as a version 1.0 `.npy` file in C order.
+pub fn to_npy_bytes(array: &NdArray) -> Vec<u8> {
+    let descr = match std::mem::size_of::<Dtype>() {
+        4 => "<f4",
+        _ => "<f8",
+    };
+    let shape = match array.shape[..] {
+        [dim] => format!("({},)", dim),
+        _ => format!(
+            "({})",
+            array
+                .shape
+                .iter()
+                .map(|dim| dim.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        descr, shape
+    );
+    // The header is padded with spaces and ends in a newline so that the data is 64-byte aligned.
+    let unpadded = NPY_MAGIC.len() + 4 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut bytes = Vec::with_capacity(10 + header.len() + array.data.len() * 8);
+    bytes.extend_from_slice(NPY_MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    for value in &array.data {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
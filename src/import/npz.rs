@@ -0,0 +1,224 @@
+//! Reader and writer for NumPy `.npz` archives: zip files of `.npy` entries, either stored
+//! (`np.savez`) or deflated (`np.savez_compressed`).
+
+use std::{collections::BTreeMap, io::Read, io::Write, path::Path};
+
+use anyhow::{Context, anyhow};
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+
+use crate::import::{
+    array::NdArray,
+    npy::{parse_npy, to_npy_bytes},
+};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+fn u16_at(bytes: &[u8], offset: usize) -> anyhow::Result<u16> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("truncated zip archive"))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> anyhow::Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("truncated zip archive"))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> anyhow::Result<u64> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("truncated zip archive"))
+}
+
+struct Entry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    local_header_offset: u64,
+}
+
+/// Replaces 32-bit sizes and offsets that overflowed with their ZIP64 extra field values.
+fn apply_zip64_extra(entry: &mut Entry, mut extra: &[u8]) -> anyhow::Result<()> {
+    while extra.len() >= 4 {
+        let id = u16_at(extra, 0)?;
+        let len = u16_at(extra, 2)? as usize;
+        let data = extra
+            .get(4..4 + len)
+            .ok_or_else(|| anyhow!("truncated zip extra field"))?;
+        if id == ZIP64_EXTRA_FIELD {
+            let mut offset = 0;
+            for value in [
+                &mut entry.uncompressed_size,
+                &mut entry.compressed_size,
+                &mut entry.local_header_offset,
+            ] {
+                if *value == u32::MAX as u64 {
+                    *value = u64_at(data, offset)?;
+                    offset += 8;
+                }
+            }
+        }
+        extra = &extra[4 + len..];
+    }
+    Ok(())
+}
+
+fn central_directory(bytes: &[u8]) -> anyhow::Result<Vec<Entry>> {
+    // The end record is at least 22 bytes long and may be followed by a comment.
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .find(|&offset| u32_at(bytes, offset).ok() == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+        .ok_or_else(|| anyhow!("not a zip archive (no end of central directory)"))?;
+    let count = u16_at(bytes, end + 10)? as usize;
+    let mut offset = u32_at(bytes, end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(bytes, offset)? != CENTRAL_HEADER_SIGNATURE {
+            return Err(anyhow!("corrupt zip central directory"));
+        }
+        let name_len = u16_at(bytes, offset + 28)? as usize;
+        let extra_len = u16_at(bytes, offset + 30)? as usize;
+        let comment_len = u16_at(bytes, offset + 32)? as usize;
+        let name_start = offset + 46;
+        let name = bytes
+            .get(name_start..name_start + name_len)
+            .ok_or_else(|| anyhow!("truncated zip archive"))?;
+        let mut entry = Entry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            method: u16_at(bytes, offset + 10)?,
+            crc: u32_at(bytes, offset + 16)?,
+            compressed_size: u32_at(bytes, offset + 20)? as u64,
+            uncompressed_size: u32_at(bytes, offset + 24)? as u64,
+            local_header_offset: u32_at(bytes, offset + 42)? as u64,
+        };
+        let extra_start = name_start + name_len;
+        let extra = bytes
+            .get(extra_start..extra_start + extra_len)
+            .ok_or_else(|| anyhow!("truncated zip archive"))?;
+        apply_zip64_extra(&mut entry, extra)?;
+        entries.push(entry);
+        offset = extra_start + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+fn entry_data(bytes: &[u8], entry: &Entry) -> anyhow::Result<Vec<u8>> {
+    let offset = entry.local_header_offset as usize;
+    if u32_at(bytes, offset)? != LOCAL_HEADER_SIGNATURE {
+        return Err(anyhow!("corrupt local header for '{}'", entry.name));
+    }
+    let start =
+        offset + 30 + u16_at(bytes, offset + 26)? as usize + u16_at(bytes, offset + 28)? as usize;
+    let compressed = bytes
+        .get(start..start + entry.compressed_size as usize)
+        .ok_or_else(|| anyhow!("truncated data for '{}'", entry.name))?;
+
+    let data = match entry.method {
+        METHOD_STORED => compressed.to_vec(),
+        METHOD_DEFLATED => {
+            let mut data = Vec::with_capacity(entry.uncompressed_size as usize);
+            DeflateDecoder::new(compressed)
+                .read_to_end(&mut data)
+                .with_context(|| format!("failed to inflate '{}'", entry.name))?;
+            data
+        }
+        other => {
+            return Err(anyhow!(
+                "'{}' uses unsupported compression method {}",
+                entry.name,
+                other
+            ));
+        }
+    };
+    if crc32fast::hash(&data) != entry.crc {
+        return Err(anyhow!("checksum mismatch for '{}'", entry.name));
+    }
+    Ok(data)
+}
+
+/// Parses all arrays of an `.npz` archive, keyed by name without the `.npy` extension.
+pub fn parse_npz(bytes: &[u8]) -> anyhow::Result<BTreeMap<String, NdArray>> {
+    central_directory(bytes)?
+        .iter()
+        .filter(|entry| entry.name.ends_with(".npy"))
+        .map(|entry| {
+            let array = entry_data(bytes, entry)
+                .and_then(|data| parse_npy(&data))
+                .with_context(|| format!("invalid array '{}'", entry.name))?;
+            Ok((entry.name.trim_end_matches(".npy").to_string(), array))
+        })
+        .collect()
+}
+
+pub fn read_npz<P: AsRef<Path>>(path: P) -> anyhow::Result<BTreeMap<String, NdArray>> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_npz(&bytes).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Encodes arrays as an `.npz` archive, deflated like `np.savez_compressed` if `compressed`.
+pub fn to_npz_bytes(arrays: &[(&str, &NdArray)], compressed: bool) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut central = Vec::new();
+    for (name, array) in arrays {
+        let name = format!("{}.npy", name);
+        let data = to_npy_bytes(array);
+        let (method, stored) = if compressed {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&data)?;
+            (METHOD_DEFLATED, encoder.finish()?)
+        } else {
+            (METHOD_STORED, data.clone())
+        };
+        let (crc, offset) = (crc32fast::hash(&data), bytes.len() as u32);
+
+        // Fields shared by the local and the central header, from "version needed" on.
+        let mut common = Vec::new();
+        common.extend_from_slice(&20u16.to_le_bytes()); // version needed to extract
+        common.extend_from_slice(&0u16.to_le_bytes()); // flags
+        common.extend_from_slice(&method.to_le_bytes());
+        common.extend_from_slice(&0u32.to_le_bytes()); // modification time and date
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+
+        bytes.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        bytes.extend_from_slice(&common);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&stored);
+
+        central.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&common);
+        central.extend_from_slice(&[0; 6]); // comment length, disk, internal attributes
+        central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = bytes.len() as u32;
+    bytes.extend_from_slice(&central);
+    bytes.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]); // disk numbers
+    bytes.extend_from_slice(&(arrays.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(arrays.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&central_offset.to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    Ok(bytes)
+}
@@ -0,0 +1,105 @@
+use anyhow::anyhow;
+
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    import::array::{NdArray, WeightLayout},
+    onnx::reader::{OnnxModel, OnnxNode, OnnxTensor},
+};
+
+impl From<&OnnxTensor> for NdArray {
+    fn from(tensor: &OnnxTensor) -> Self {
+        NdArray {
+            shape: tensor.dims.clone(),
+            data: tensor.data.iter().map(|v| *v as Dtype).collect(),
+        }
+    }
+}
+
+fn initializer<'a>(
+    model: &'a OnnxModel,
+    node: &OnnxNode,
+    index: usize,
+) -> anyhow::Result<&'a OnnxTensor> {
+    let name = node
+        .inputs
+        .get(index)
+        .ok_or_else(|| anyhow!("node '{}' has no input {}", node.name, index))?;
+    model.initializer(name).ok_or_else(|| {
+        anyhow!(
+            "input '{}' of node '{}' is not an initializer",
+            name,
+            node.name
+        )
+    })
+}
+
+/// Weights `(output_size, input_size)` and biases `(output_size, 1)` of every fully connected
+/// layer of a simple MLP, in graph order.
+///
+/// A layer is either a `Gemm` node (`transB` decides whether `B` is `[out, in]` or `[in, out]`)
+/// or a `MatMul` with a `[in, out]` initializer, optionally followed by an `Add` of the bias.
+/// Activations are not imported.
+pub fn dense_parameters(model: &OnnxModel) -> anyhow::Result<Vec<(Matrix, Matrix)>> {
+    let mut parameters = Vec::new();
+    for node in &model.nodes {
+        let layer = match node.op_type.as_str() {
+            "Gemm" => {
+                if node.int_attribute("transA", 0) != 0
+                    || node.float_attribute("alpha", 1.0) != 1.0
+                    || node.float_attribute("beta", 1.0) != 1.0
+                {
+                    return Err(anyhow!(
+                        "Gemm node '{}' uses transA, alpha or beta, which a dense layer cannot express",
+                        node.name
+                    ));
+                }
+                let layout = if node.int_attribute("transB", 0) != 0 {
+                    WeightLayout::OutputInput
+                } else {
+                    WeightLayout::InputOutput
+                };
+                let weights = NdArray::from(initializer(model, node, 1)?).to_weights(layout)?;
+                let biases = match node.inputs.get(2) {
+                    Some(_) => NdArray::from(initializer(model, node, 2)?).to_biases()?,
+                    None => Matrix::new(weights.rows, 1),
+                };
+                (weights, biases)
+            }
+            "MatMul" => {
+                let weights = NdArray::from(initializer(model, node, 1)?)
+                    .to_weights(WeightLayout::InputOutput)?;
+                // The bias is the initializer operand of an `Add` that takes the product, in
+                // either position.
+                let output = node.outputs.first();
+                let bias = model.nodes.iter().find_map(|other| {
+                    if other.op_type != "Add" || other.inputs.len() != 2 {
+                        return None;
+                    }
+                    let position = other
+                        .inputs
+                        .iter()
+                        .position(|input| Some(input) == output)?;
+                    model.initializer(&other.inputs[1 - position])
+                });
+                let biases = match bias {
+                    Some(bias) => NdArray::from(bias).to_biases()?,
+                    None => Matrix::new(weights.rows, 1),
+                };
+                (weights, biases)
+            }
+            _ => continue,
+        };
+
+        if layer.1.rows != layer.0.rows {
+            return Err(anyhow!(
+                "node '{}' has {} outputs but {} biases",
+                node.name,
+                layer.0.rows,
+                layer.1.rows
+            ));
+        }
+        parameters.push(layer);
+    }
+    Ok(parameters)
+}
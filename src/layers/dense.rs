@@ -1,9 +1,12 @@
+use crate::{Dtype, data_structures::matrix::Matrix, layers::Layer};
+#[cfg(feature = "train")]
 use crate::{
-    Dtype, SEED,
-    data_structures::matrix::{Matrix, sum_cols},
+    SEED,
+    data_structures::matrix::sum_cols,
     layers::{
-        Layer,
+        Parameter,
         optimizers::{Optimizer, adam::Adam},
+        regularization::Regularizer,
     },
 };
 
@@ -11,51 +14,143 @@ pub struct DenseLayer {
     weights: Matrix, // rows: output_size, cols: input_size
     biases: Matrix,  // rows: output_size, cols: 1
 
+    #[cfg(feature = "train")]
     input_cache: Matrix,
-    optimizer: Box<dyn Optimizer>,
+    #[cfg(feature = "train")]
+    weights_gradient: Matrix,
+    #[cfg(feature = "train")]
+    biases_gradient: Matrix,
+    // Only `None` while `wrap_optimizer` moves it into its wrapper.
+    #[cfg(feature = "train")]
+    optimizer: Option<Box<dyn Optimizer>>,
+    #[cfg(feature = "train")]
+    regularizer: Regularizer,
 }
 
 pub struct ConfigDenseLayer {
     pub learning_rate: Dtype,
     pub momentum_factor: Dtype,
+    /// L2 penalty on the weights, applied by the layer's `Regularizer`.
     pub weight_decay: Dtype,
 }
 
+impl Default for ConfigDenseLayer {
+    fn default() -> Self {
+        ConfigDenseLayer {
+            learning_rate: 0.001,
+            momentum_factor: 0.0,
+            weight_decay: 0.0,
+        }
+    }
+}
+
+#[cfg(feature = "train")]
+impl ConfigDenseLayer {
+    /// The default optimizer of a dense layer: `Adam` with the configured learning rate.
+    pub fn build_optimizer(&self, input_size: usize, output_size: usize) -> Box<dyn Optimizer> {
+        Box::new(Adam::new(
+            self.learning_rate,
+            0.9,
+            0.999,
+            1e-8,
+            input_size,
+            output_size,
+        ))
+    }
+
+    /// A dense layer with the given weights `(output_size, input_size)` and biases, trained by
+    /// the default optimizer with this configuration and its L2 penalty.
+    pub fn build_layer(&self, weights: Matrix, biases: Matrix) -> DenseLayer {
+        let optimizer = self.build_optimizer(weights.cols, weights.rows);
+        DenseLayer::from_parameters(weights, biases, optimizer)
+            .with_regularizer(Regularizer::l2(self.weight_decay))
+    }
+}
+
+impl DenseLayer {
+    /// Creates a layer from trained weights `(output_size, input_size)` and biases `(output_size, 1)`.
+    /// With the `train` feature it is updated by the default optimizer.
+    pub fn from_weights(weights: Matrix, biases: Matrix) -> DenseLayer {
+        #[cfg(feature = "train")]
+        {
+            let optimizer = ConfigDenseLayer::default().build_optimizer(weights.cols, weights.rows);
+            DenseLayer::from_parameters(weights, biases, optimizer)
+        }
+        #[cfg(not(feature = "train"))]
+        {
+            assert_eq!(
+                (biases.rows, biases.cols),
+                (weights.rows, 1),
+                "biases must have shape (output_size, 1)"
+            );
+            DenseLayer { weights, biases }
+        }
+    }
+}
+
+#[cfg(feature = "train")]
 impl DenseLayer {
     pub fn new(
         input_size: usize,
         output_size: usize,
         config: &ConfigDenseLayer,
     ) -> DenseLayer {
-        let weights = Matrix::new_seeded_random(output_size, input_size, SEED);
-        let biases = Matrix::new_seeded_random(output_size, 1, SEED);
-
         // let optimizers = Box::new(AdaGrad::new(
         //     config.learning_rate,
         //     1e-8,
         //     config.momentum_factor,
-        //     config.weight_decay,
         //     input_size,
         //     output_size,
         // ));
 
-        let optimizers = Box::new(Adam::new(
-            config.learning_rate,
-            0.9,
-            0.999,
-            1e-8,
-            config.weight_decay,
-            input_size,
-            output_size,
-        ));
+        config.build_layer(
+            Matrix::new_seeded_random(output_size, input_size, SEED),
+            Matrix::new_seeded_random(output_size, 1, SEED),
+        )
+    }
+
+    /// Creates a layer that is updated by the given optimizer instead of the default `Adam`.
+    pub fn with_optimizer(
+        input_size: usize,
+        output_size: usize,
+        optimizer: Box<dyn Optimizer>,
+    ) -> DenseLayer {
+        let weights = Matrix::new_seeded_random(output_size, input_size, SEED);
+        let biases = Matrix::new_seeded_random(output_size, 1, SEED);
+
+        DenseLayer::from_parameters(weights, biases, optimizer)
+    }
+
+    /// Creates a layer from existing weights `(output_size, input_size)` and biases `(output_size, 1)`.
+    pub fn from_parameters(
+        weights: Matrix,
+        biases: Matrix,
+        optimizer: Box<dyn Optimizer>,
+    ) -> DenseLayer {
+        assert_eq!(
+            (biases.rows, biases.cols),
+            (weights.rows, 1),
+            "biases must have shape (output_size, 1)"
+        );
+        let (output_size, input_size) = (weights.rows, weights.cols);
 
         DenseLayer {
             weights,
             biases,
             input_cache: Matrix::new(0, 0),
-            optimizer: optimizers,
+            weights_gradient: Matrix::new(output_size, input_size),
+            biases_gradient: Matrix::new(output_size, 1),
+            optimizer: Some(optimizer),
+            regularizer: Regularizer::default(),
         }
     }
+
+    /// Regularizes the weights of this layer with penalties and/or a norm constraint. Replaces
+    /// the L2 penalty set from `ConfigDenseLayer::weight_decay`.
+    pub fn with_regularizer(mut self, regularizer: Regularizer) -> DenseLayer {
+        self.regularizer = regularizer;
+        self
+    }
 }
 
 impl Layer for DenseLayer {
@@ -67,7 +162,10 @@ impl Layer for DenseLayer {
     }
 
     fn forward(&mut self, input: &Matrix) -> Matrix {
-        self.input_cache = input.clone();
+        #[cfg(feature = "train")]
+        {
+            self.input_cache = input.clone();
+        }
 
         let batch_size = input.cols;
         let mut output = &self.weights * input;
@@ -81,6 +179,7 @@ impl Layer for DenseLayer {
         output
     }
 
+    #[cfg(feature = "train")]
     fn backward(&mut self, output_gradient: &Matrix) -> Matrix {
         let batch_size = self.input_cache.cols as Dtype;
 
@@ -94,13 +193,70 @@ impl Layer for DenseLayer {
         let raw_biases_gradient = sum_cols(output_gradient);
         let current_gradient_b = &raw_biases_gradient * (1.0 / batch_size);
 
-        (self.weights, self.biases) = self.optimizer.update(
+        self.weights_gradient = &self.weights_gradient + &current_gradient_w;
+        self.biases_gradient = &self.biases_gradient + &current_gradient_b;
+
+        input_gradient
+    }
+
+    #[cfg(feature = "train")]
+    fn parameters_mut(&mut self) -> Vec<Parameter<'_>> {
+        vec![
+            Parameter {
+                value: &mut self.weights,
+                gradient: &mut self.weights_gradient,
+            },
+            Parameter {
+                value: &mut self.biases,
+                gradient: &mut self.biases_gradient,
+            },
+        ]
+    }
+
+    #[cfg(feature = "train")]
+    fn step(&mut self) {
+        // The penalty gradient is added once per step, independent of gradient accumulation.
+        let mut weights_gradient = self.weights_gradient.clone();
+        self.regularizer.add_gradient(&self.weights, &mut weights_gradient);
+
+        let optimizer = self.optimizer.as_mut().expect("dense layer without optimizer");
+        (self.weights, self.biases) = optimizer.update(
             &self.weights,
             &self.biases,
-            current_gradient_w,
-            current_gradient_b,
+            weights_gradient,
+            self.biases_gradient.clone(),
         );
 
-        input_gradient
+        self.regularizer.apply_constraint(&mut self.weights);
+    }
+
+    #[cfg(feature = "train")]
+    fn regularization_loss(&self) -> Dtype {
+        self.regularizer.penalty(&self.weights)
+    }
+
+    #[cfg(feature = "train")]
+    fn optimizer(&self) -> Option<&dyn Optimizer> {
+        self.optimizer.as_deref()
+    }
+
+    #[cfg(feature = "train")]
+    fn optimizer_mut(&mut self) -> Option<&mut dyn Optimizer> {
+        match self.optimizer.as_mut() {
+            Some(optimizer) => Some(optimizer.as_mut()),
+            None => None,
+        }
+    }
+
+    #[cfg(feature = "train")]
+    fn wrap_optimizer(
+        &mut self,
+        wrap: &dyn Fn(Box<dyn Optimizer>, usize, usize) -> Box<dyn Optimizer>,
+    ) {
+        let (input_size, output_size) = (self.weights.cols, self.weights.rows);
+        self.optimizer = self
+            .optimizer
+            .take()
+            .map(|optimizer| wrap(optimizer, input_size, output_size));
     }
 }
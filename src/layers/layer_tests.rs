@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        data_structures::matrix::Matrix,
+        layers::{
+            Layer,
+            dense::DenseLayer,
+            forward_all,
+            regularization::{Regularizer, WeightConstraint},
+            relu::ReLULayer,
+            softmax::Softmax,
+        },
+    };
+
+    fn weights() -> Matrix {
+        let mut w = Matrix::new(2, 2);
+        w.set(0, 0, 3.0);
+        w.set(0, 1, -4.0);
+        w.set(1, 0, 0.0);
+        w.set(1, 1, 0.5);
+        w
+    }
+
+    #[test]
+    fn test_elastic_net_penalty_and_gradient() {
+        let reg = Regularizer::elastic_net(0.1, 0.2);
+        let w = weights();
+
+        // l1: 0.1 * 7.5, l2: 0.5 * 0.2 * 25.25
+        assert!((reg.penalty(&w) - (0.75 + 2.525)).abs() < 1e-5);
+
+        let mut grad = Matrix::new(2, 2);
+        reg.add_gradient(&w, &mut grad);
+        assert!((grad.get(0, 0) - (0.1 + 0.6)).abs() < 1e-6);
+        assert!((grad.get(0, 1) - (-0.1 - 0.8)).abs() < 1e-6);
+        assert_eq!(grad.get(1, 0), 0.0);
+    }
+
+    #[test]
+    fn test_max_norm_constraint() {
+        let reg = Regularizer::default().with_constraint(WeightConstraint::MaxNorm(1.0));
+        let mut w = weights();
+        reg.apply_constraint(&mut w);
+
+        // Row 0 has norm 5 and is scaled down, row 1 is already inside the ball.
+        assert!((w.get(0, 0) - 0.6).abs() < 1e-6);
+        assert!((w.get(0, 1) + 0.8).abs() < 1e-6);
+        assert_eq!(w.get(1, 1), 0.5);
+    }
+
+    #[test]
+    fn test_unit_norm_constraint() {
+        let reg = Regularizer::l2(0.0).with_constraint(WeightConstraint::UnitNorm);
+        let mut w = weights();
+        reg.apply_constraint(&mut w);
+
+        assert!((w.get(0, 0) - 0.6).abs() < 1e-6);
+        assert!((w.get(1, 1) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_forward_all_from_weights() {
+        let mut biases = Matrix::new(2, 1);
+        biases.set(0, 0, 1.0);
+        let mut layers: Vec<Box<dyn Layer>> = vec![
+            Box::new(DenseLayer::from_weights(weights(), biases)),
+            Box::new(ReLULayer::new()),
+            Box::new(Softmax::new()),
+        ];
+        let mut input = Matrix::new(2, 1);
+        input.set(0, 0, 1.0);
+        input.set(1, 0, 1.0);
+
+        // Dense: [3 - 4 + 1, 0.5] = [0, 0.5], ReLU keeps it, softmax normalizes.
+        let output = forward_all(&mut layers, &input);
+        let expected = 1.0 / (1.0 + 0.5f32.exp());
+        assert!((output.get(0, 0) - expected).abs() < 1e-6);
+        assert!((output.get(0, 0) + output.get(1, 0) - 1.0).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "train")]
+    #[test]
+    fn test_weight_decay_is_applied_once_by_the_regularizer() {
+        use crate::layers::dense::ConfigDenseLayer;
+
+        let config = |weight_decay| ConfigDenseLayer {
+            learning_rate: 0.1,
+            momentum_factor: 0.0,
+            weight_decay,
+        };
+        let mut input = Matrix::new(3, 2);
+        input.data.copy_from_slice(&[1.0, -2.0, 0.5, 0.0, 1.0, 3.0]);
+        let trained_weights = |mut layer: DenseLayer| {
+            layer.forward(&input);
+            layer.backward(&Matrix::new(2, 2));
+            layer.step();
+            layer.get_weights().unwrap().clone()
+        };
+
+        // With a zero loss gradient only the decay moves the weights.
+        let decayed = trained_weights(DenseLayer::new(3, 2, &config(0.1)));
+        let regularized = trained_weights(
+            DenseLayer::new(3, 2, &config(0.0)).with_regularizer(Regularizer::l2(0.1)),
+        );
+        let both = trained_weights(
+            DenseLayer::new(3, 2, &config(0.1)).with_regularizer(Regularizer::l2(0.1)),
+        );
+        let plain = trained_weights(DenseLayer::new(3, 2, &config(0.0)));
+
+        assert_ne!(decayed.data, plain.data);
+        assert_eq!(decayed.data, regularized.data);
+        assert_eq!(decayed.data, both.data);
+    }
+}
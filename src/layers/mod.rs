@@ -1,15 +1,89 @@
+use alloc::boxed::Box;
+use core::any::Any;
+
 use crate::data_structures::matrix::Matrix;
+#[cfg(feature = "train")]
+use crate::{Dtype, layers::optimizers::Optimizer};
 
 pub mod dense;
 pub mod softmax;
 pub mod relu;
+#[cfg(feature = "train")]
 pub mod optimizers;
+#[cfg(feature = "train")]
+pub mod layer_tests;
+#[cfg(feature = "train")]
+pub mod regularization;
 
+/// A trainable tensor of a layer together with the gradient accumulated for it.
+#[cfg(feature = "train")]
+pub struct Parameter<'a> {
+    pub value: &'a mut Matrix,
+    pub gradient: &'a mut Matrix,
+}
 
-pub trait Layer {
+/// A layer of a feed-forward network. Without the `train` feature only the forward pass exists.
+/// `Any` lets the model formats recognize the layer types of this crate.
+pub trait Layer: Any {
     fn get_weights(&self) -> Option<&Matrix>;
     fn get_biases(&self) -> Option<&Matrix>;
     fn forward(&mut self, input: &Matrix) -> Matrix;
 
+    /// Computes the gradient with respect to the input and adds the parameter gradients
+    /// to the accumulated ones. The parameters themselves are only changed by `step`.
+    #[cfg(feature = "train")]
     fn backward(&mut self, output_gradient: &Matrix) -> Matrix;
+
+    /// Trainable parameters with their accumulated gradients, empty for stateless layers.
+    #[cfg(feature = "train")]
+    fn parameters_mut(&mut self) -> Vec<Parameter<'_>> {
+        Vec::new()
+    }
+
+    /// Resets the accumulated gradients to zero.
+    #[cfg(feature = "train")]
+    fn zero_grad(&mut self) {
+        for parameter in self.parameters_mut() {
+            parameter.gradient.data.fill(0.0);
+        }
+    }
+
+    /// Lets the optimizer update the parameters using the accumulated gradients.
+    #[cfg(feature = "train")]
+    fn step(&mut self) {}
+
+    /// Regularization penalty of the current parameters, added to the loss if requested.
+    #[cfg(feature = "train")]
+    fn regularization_loss(&self) -> Dtype {
+        0.0
+    }
+
+    /// The optimizer updating this layer's parameters, if it has any.
+    #[cfg(feature = "train")]
+    fn optimizer(&self) -> Option<&dyn Optimizer> {
+        None
+    }
+
+    #[cfg(feature = "train")]
+    fn optimizer_mut(&mut self) -> Option<&mut dyn Optimizer> {
+        None
+    }
+
+    /// Replaces the layer's optimizer with `wrap(optimizer, input_size, output_size)`, e.g. to
+    /// add a `Lookahead`. Layers without parameters ignore it.
+    #[cfg(feature = "train")]
+    fn wrap_optimizer(
+        &mut self,
+        _wrap: &dyn Fn(Box<dyn Optimizer>, usize, usize) -> Box<dyn Optimizer>,
+    ) {
+    }
+}
+
+/// Runs `input` through all layers in order.
+pub fn forward_all(layers: &mut [Box<dyn Layer>], input: &Matrix) -> Matrix {
+    let mut output = input.clone();
+    for layer in layers.iter_mut() {
+        output = layer.forward(&output);
+    }
+    output
 }
\ No newline at end of file
@@ -1,4 +1,8 @@
-use crate::{Dtype, data_structures::matrix::Matrix, layers::optimizers::Optimizer};
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::optimizers::{Optimizer, state::OptimizerState},
+};
 
 pub struct AdaGrad {
     learning_rate: Dtype,
@@ -11,7 +15,6 @@ pub struct AdaGrad {
     velocity_b: Matrix,
 
     momentum_factor: Dtype,
-    weight_decay: Dtype,
 }
 
 impl AdaGrad {
@@ -19,7 +22,6 @@ impl AdaGrad {
         learning_rate: Dtype,
         epsilon: Dtype,
         momentum_factor: Dtype,
-        weight_decay: Dtype,
         input_size: usize,
         output_size: usize,
     ) -> AdaGrad {
@@ -34,7 +36,6 @@ impl AdaGrad {
             grad_accum_b: Matrix::new(output_size, 1),
 
             momentum_factor,
-            weight_decay,
         }
     }
 }
@@ -44,17 +45,11 @@ impl Optimizer for AdaGrad {
         &mut self,
         weights: &Matrix,
         biases: &Matrix,
-        mut weights_gradients: Matrix,
+        weights_gradients: Matrix,
         bias_gradients: Matrix,
     ) -> (Matrix, Matrix) {
-        // 3. Weight decay (L2)
-        if self.weight_decay > 0.0 {
-            let l2_grad_w = &*weights * self.weight_decay;
-            weights_gradients = &weights_gradients + &l2_grad_w;
-        }
-
         // ----------------------------
-        // 4. AdaGrad adjustment
+        // 1. AdaGrad adjustment
         // ----------------------------
         let grad_w_sq = weights_gradients.element_wise_mul(&weights_gradients);
         let grad_b_sq = bias_gradients.element_wise_mul(&bias_gradients);
@@ -73,11 +68,38 @@ impl Optimizer for AdaGrad {
             * self.learning_rate;
 
         // ----------------------------
-        // 5. Momentum
+        // 2. Momentum
         // ----------------------------
-        // self.velocity_w = &self.velocity_w * self.momentum_factor + ada_lr_w;
-        // self.velocity_b = &self.velocity_b * self.momentum_factor + ada_lr_b;
+        self.velocity_w = &self.velocity_w * self.momentum_factor + ada_lr_w;
+        self.velocity_b = &self.velocity_b * self.momentum_factor + ada_lr_b;
+
+        (weights - &self.velocity_w, biases - &self.velocity_b)
+    }
+
+    fn learning_rate(&self) -> Dtype {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: Dtype) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state(&self) -> OptimizerState {
+        OptimizerState::new("AdaGrad")
+            .with_scalar("learning_rate", self.learning_rate as f64)
+            .with_tensor("grad_accum_w", &self.grad_accum_w)
+            .with_tensor("grad_accum_b", &self.grad_accum_b)
+            .with_tensor("velocity_w", &self.velocity_w)
+            .with_tensor("velocity_b", &self.velocity_b)
+    }
 
-        (weights - &ada_lr_w, biases - &ada_lr_b)
+    fn load_state(&mut self, mut state: OptimizerState) -> anyhow::Result<()> {
+        state.expect_kind("AdaGrad")?;
+        self.learning_rate = state.scalar("learning_rate")? as Dtype;
+        self.grad_accum_w = state.take_tensor("grad_accum_w", &self.grad_accum_w)?;
+        self.grad_accum_b = state.take_tensor("grad_accum_b", &self.grad_accum_b)?;
+        self.velocity_w = state.take_tensor("velocity_w", &self.velocity_w)?;
+        self.velocity_b = state.take_tensor("velocity_b", &self.velocity_b)?;
+        Ok(())
     }
 }
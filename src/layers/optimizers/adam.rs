@@ -1,4 +1,8 @@
-use crate::{Dtype, data_structures::matrix::Matrix, layers::optimizers::Optimizer};
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::optimizers::{Optimizer, state::OptimizerState},
+};
 
 pub struct Adam {
     learning_rate: Dtype,
@@ -18,8 +22,6 @@ pub struct Adam {
 
     // Time step counter for bias correction
     t: Dtype,
-
-    weight_decay: Dtype,
 }
 // Note: Changed struct name from AdaGrad to Adam
 
@@ -29,7 +31,6 @@ impl Adam {
         beta1: Dtype,   // Typically 0.9
         beta2: Dtype,   // Typically 0.999
         epsilon: Dtype, // Typically 1e-8
-        weight_decay: Dtype,
         input_size: usize,
         output_size: usize,
     ) -> Adam {
@@ -47,7 +48,6 @@ impl Adam {
             v_b: Matrix::new(output_size, 1),
 
             t: 0.0, // Initial time step
-            weight_decay,
         }
     }
 }
@@ -59,20 +59,14 @@ impl Optimizer for Adam {
         &mut self,
         weights: &Matrix,
         biases: &Matrix,
-        mut weights_gradients: Matrix,
+        weights_gradients: Matrix,
         bias_gradients: Matrix,
     ) -> (Matrix, Matrix) {
         // 1. Time Step Increment
         self.t += 1.0;
         let t_us = self.t;
 
-        // 2. Weight decay (L2) - Applied to the gradient
-        if self.weight_decay > 0.0 {
-            let l2_grad_w = &*weights * self.weight_decay;
-            weights_gradients = &weights_gradients + &l2_grad_w;
-        }
-
-        // --- 3. Moment Estimates (m and v) ---
+        // --- 2. Moment Estimates (m and v) ---
         // New gradients (g) are weights_gradients and bias_gradients
         let g_w_sq = weights_gradients.element_wise_mul(&weights_gradients);
         let g_b_sq = bias_gradients.element_wise_mul(&bias_gradients);
@@ -85,7 +79,7 @@ impl Optimizer for Adam {
         self.v_w = (&self.v_w * self.beta2) + (&g_w_sq * (1.0 - self.beta2));
         self.v_b = (&self.v_b * self.beta2) + (&g_b_sq * (1.0 - self.beta2));
 
-        // --- 4. Bias Correction ---
+        // --- 3. Bias Correction ---
         // m_hat = m_t / (1 - beta1^t)
         let beta1_t = self.beta1.powf(t_us);
         let beta2_t = self.beta2.powf(t_us);
@@ -97,7 +91,7 @@ impl Optimizer for Adam {
         let v_hat_w = &self.v_w / (1.0 - beta2_t);
         let v_hat_b = &self.v_b / (1.0 - beta2_t);
 
-        // --- 5. Parameter Update ---
+        // --- 4. Parameter Update ---
         // theta_t+1 = theta_t - LR * [ m_hat / (sqrt(v_hat) + epsilon) ]
         let lr = self.learning_rate;
         let eps = self.epsilon;
@@ -112,4 +106,33 @@ impl Optimizer for Adam {
 
         (weights - &step_w, biases - &step_b)
     }
+
+    fn learning_rate(&self) -> Dtype {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: Dtype) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state(&self) -> OptimizerState {
+        OptimizerState::new("Adam")
+            .with_scalar("learning_rate", self.learning_rate as f64)
+            .with_scalar("t", self.t as f64)
+            .with_tensor("m_w", &self.m_w)
+            .with_tensor("m_b", &self.m_b)
+            .with_tensor("v_w", &self.v_w)
+            .with_tensor("v_b", &self.v_b)
+    }
+
+    fn load_state(&mut self, mut state: OptimizerState) -> anyhow::Result<()> {
+        state.expect_kind("Adam")?;
+        self.learning_rate = state.scalar("learning_rate")? as Dtype;
+        self.t = state.scalar("t")? as Dtype;
+        self.m_w = state.take_tensor("m_w", &self.m_w)?;
+        self.m_b = state.take_tensor("m_b", &self.m_b)?;
+        self.v_w = state.take_tensor("v_w", &self.v_w)?;
+        self.v_b = state.take_tensor("v_b", &self.v_b)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,158 @@
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::optimizers::{Optimizer, state::OptimizerState},
+};
+
+/// Adam with decoupled weight decay (Loshchilov & Hutter, "Decoupled Weight Decay Regularization").
+///
+/// Unlike `Adam`, the decay is not added to the gradient (where it would be rescaled by the
+/// adaptive denominator) but applied directly to the parameters: `theta -= lr * wd * theta`.
+pub struct AdamW {
+    learning_rate: Dtype,
+    epsilon: Dtype,
+
+    // First moment
+    m_w: Matrix,
+    m_b: Matrix,
+
+    // Second moment
+    v_w: Matrix,
+    v_b: Matrix,
+
+    beta1: Dtype,
+    beta2: Dtype,
+
+    // Time step counter for bias correction
+    t: Dtype,
+
+    weight_decay: Dtype,
+
+    // Which parameters the decay applies to. Biases are excluded by default.
+    decay_weights: bool,
+    decay_biases: bool,
+}
+
+impl AdamW {
+    pub fn new(
+        learning_rate: Dtype,
+        beta1: Dtype,   // Typically 0.9
+        beta2: Dtype,   // Typically 0.999
+        epsilon: Dtype, // Typically 1e-8
+        weight_decay: Dtype,
+        input_size: usize,
+        output_size: usize,
+    ) -> AdamW {
+        AdamW {
+            learning_rate,
+            beta1,
+            beta2,
+            epsilon,
+
+            m_w: Matrix::new(output_size, input_size),
+            m_b: Matrix::new(output_size, 1),
+
+            v_w: Matrix::new(output_size, input_size),
+            v_b: Matrix::new(output_size, 1),
+
+            t: 0.0,
+            weight_decay,
+
+            decay_weights: true,
+            decay_biases: false,
+        }
+    }
+
+    /// Enables or disables weight decay on the weights matrix.
+    /// Disable it when the weights act as normalization gains, which should not be pulled to zero.
+    pub fn decay_weights(mut self, enabled: bool) -> AdamW {
+        self.decay_weights = enabled;
+        self
+    }
+
+    /// Enables or disables weight decay on the biases (disabled by default).
+    pub fn decay_biases(mut self, enabled: bool) -> AdamW {
+        self.decay_biases = enabled;
+        self
+    }
+}
+
+impl Optimizer for AdamW {
+    fn update(
+        &mut self,
+        weights: &Matrix,
+        biases: &Matrix,
+        weights_gradients: Matrix,
+        bias_gradients: Matrix,
+    ) -> (Matrix, Matrix) {
+        self.t += 1.0;
+        let t_us = self.t;
+
+        // --- 1. Moment Estimates (m and v), on the raw gradient only ---
+        let g_w_sq = weights_gradients.element_wise_mul(&weights_gradients);
+        let g_b_sq = bias_gradients.element_wise_mul(&bias_gradients);
+
+        self.m_w = (&self.m_w * self.beta1) + (&weights_gradients * (1.0 - self.beta1));
+        self.m_b = (&self.m_b * self.beta1) + (&bias_gradients * (1.0 - self.beta1));
+
+        self.v_w = (&self.v_w * self.beta2) + (&g_w_sq * (1.0 - self.beta2));
+        self.v_b = (&self.v_b * self.beta2) + (&g_b_sq * (1.0 - self.beta2));
+
+        // --- 2. Bias Correction ---
+        let beta1_t = self.beta1.powf(t_us);
+        let beta2_t = self.beta2.powf(t_us);
+
+        let m_hat_w = &self.m_w / (1.0 - beta1_t);
+        let m_hat_b = &self.m_b / (1.0 - beta1_t);
+
+        let v_hat_w = &self.v_w / (1.0 - beta2_t);
+        let v_hat_b = &self.v_b / (1.0 - beta2_t);
+
+        // --- 3. Decoupled weight decay ---
+        // theta <- theta * (1 - LR * wd), applied before the adaptive step
+        let lr = self.learning_rate;
+        let eps = self.epsilon;
+        let decay = 1.0 - lr * self.weight_decay;
+
+        let decayed_w = if self.decay_weights { weights * decay } else { weights.clone() };
+        let decayed_b = if self.decay_biases { biases * decay } else { biases.clone() };
+
+        // --- 4. Parameter Update ---
+        let denom_w = &v_hat_w.element_wise_sqrt() + eps;
+        let step_w = (m_hat_w.element_wise_div(&denom_w)) * lr;
+
+        let denom_b = &v_hat_b.element_wise_sqrt() + eps;
+        let step_b = (m_hat_b.element_wise_div(&denom_b)) * lr;
+
+        (&decayed_w - &step_w, &decayed_b - &step_b)
+    }
+
+    fn learning_rate(&self) -> Dtype {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: Dtype) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state(&self) -> OptimizerState {
+        OptimizerState::new("AdamW")
+            .with_scalar("learning_rate", self.learning_rate as f64)
+            .with_scalar("t", self.t as f64)
+            .with_tensor("m_w", &self.m_w)
+            .with_tensor("m_b", &self.m_b)
+            .with_tensor("v_w", &self.v_w)
+            .with_tensor("v_b", &self.v_b)
+    }
+
+    fn load_state(&mut self, mut state: OptimizerState) -> anyhow::Result<()> {
+        state.expect_kind("AdamW")?;
+        self.learning_rate = state.scalar("learning_rate")? as Dtype;
+        self.t = state.scalar("t")? as Dtype;
+        self.m_w = state.take_tensor("m_w", &self.m_w)?;
+        self.m_b = state.take_tensor("m_b", &self.m_b)?;
+        self.v_w = state.take_tensor("v_w", &self.v_w)?;
+        self.v_b = state.take_tensor("v_b", &self.v_b)?;
+        Ok(())
+    }
+}
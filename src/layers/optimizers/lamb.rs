@@ -0,0 +1,139 @@
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::optimizers::{Optimizer, lars::norm, state::OptimizerState},
+};
+
+/// LAMB: Adam with layer-wise adaptive trust ratios (You et al., "Large Batch Optimization
+/// for Deep Learning: Training BERT in 76 minutes").
+///
+/// The Adam direction `r = m_hat / (sqrt(v_hat) + eps)` plus the decoupled decay `wd * w`
+/// is rescaled by `||w|| / ||r + wd * w||`. The biases follow the unscaled Adam direction
+/// and are not decayed.
+pub struct Lamb {
+    learning_rate: Dtype,
+    epsilon: Dtype,
+
+    m_w: Matrix,
+    m_b: Matrix,
+
+    v_w: Matrix,
+    v_b: Matrix,
+
+    beta1: Dtype,
+    beta2: Dtype,
+
+    // Time step counter for bias correction
+    t: Dtype,
+
+    weight_decay: Dtype,
+}
+
+impl Lamb {
+    pub fn new(
+        learning_rate: Dtype,
+        beta1: Dtype,   // Typically 0.9
+        beta2: Dtype,   // Typically 0.999
+        epsilon: Dtype, // Typically 1e-6
+        weight_decay: Dtype,
+        input_size: usize,
+        output_size: usize,
+    ) -> Lamb {
+        Lamb {
+            learning_rate,
+            beta1,
+            beta2,
+            epsilon,
+
+            m_w: Matrix::new(output_size, input_size),
+            m_b: Matrix::new(output_size, 1),
+
+            v_w: Matrix::new(output_size, input_size),
+            v_b: Matrix::new(output_size, 1),
+
+            t: 0.0,
+            weight_decay,
+        }
+    }
+}
+
+impl Optimizer for Lamb {
+    fn update(
+        &mut self,
+        weights: &Matrix,
+        biases: &Matrix,
+        weights_gradients: Matrix,
+        bias_gradients: Matrix,
+    ) -> (Matrix, Matrix) {
+        self.t += 1.0;
+
+        // --- 1. Moment Estimates (m and v) ---
+        let g_w_sq = weights_gradients.element_wise_mul(&weights_gradients);
+        let g_b_sq = bias_gradients.element_wise_mul(&bias_gradients);
+
+        self.m_w = (&self.m_w * self.beta1) + (&weights_gradients * (1.0 - self.beta1));
+        self.m_b = (&self.m_b * self.beta1) + (&bias_gradients * (1.0 - self.beta1));
+
+        self.v_w = (&self.v_w * self.beta2) + (&g_w_sq * (1.0 - self.beta2));
+        self.v_b = (&self.v_b * self.beta2) + (&g_b_sq * (1.0 - self.beta2));
+
+        // --- 2. Bias Correction ---
+        let beta1_t = self.beta1.powf(self.t);
+        let beta2_t = self.beta2.powf(self.t);
+
+        let m_hat_w = &self.m_w / (1.0 - beta1_t);
+        let m_hat_b = &self.m_b / (1.0 - beta1_t);
+
+        let v_hat_w = &self.v_w / (1.0 - beta2_t);
+        let v_hat_b = &self.v_b / (1.0 - beta2_t);
+
+        // --- 3. Adam direction plus decoupled weight decay ---
+        let eps = self.epsilon;
+        let update_w = &m_hat_w.element_wise_div(&(&v_hat_w.element_wise_sqrt() + eps))
+            + &(weights * self.weight_decay);
+        let update_b = m_hat_b.element_wise_div(&(&v_hat_b.element_wise_sqrt() + eps));
+
+        // --- 4. Layer-wise trust ratio ---
+        let w_norm = norm(weights);
+        let u_norm = norm(&update_w);
+        let trust_ratio = if w_norm > 0.0 && u_norm > 0.0 {
+            w_norm / u_norm
+        } else {
+            1.0
+        };
+
+        let step_w = update_w * (self.learning_rate * trust_ratio);
+        let step_b = update_b * self.learning_rate;
+
+        (weights - &step_w, biases - &step_b)
+    }
+
+    fn learning_rate(&self) -> Dtype {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: Dtype) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state(&self) -> OptimizerState {
+        OptimizerState::new("Lamb")
+            .with_scalar("learning_rate", self.learning_rate as f64)
+            .with_scalar("t", self.t as f64)
+            .with_tensor("m_w", &self.m_w)
+            .with_tensor("m_b", &self.m_b)
+            .with_tensor("v_w", &self.v_w)
+            .with_tensor("v_b", &self.v_b)
+    }
+
+    fn load_state(&mut self, mut state: OptimizerState) -> anyhow::Result<()> {
+        state.expect_kind("Lamb")?;
+        self.learning_rate = state.scalar("learning_rate")? as Dtype;
+        self.t = state.scalar("t")? as Dtype;
+        self.m_w = state.take_tensor("m_w", &self.m_w)?;
+        self.m_b = state.take_tensor("m_b", &self.m_b)?;
+        self.v_w = state.take_tensor("v_w", &self.v_w)?;
+        self.v_b = state.take_tensor("v_b", &self.v_b)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::optimizers::{Optimizer, state::OptimizerState},
+};
+
+/// LARS: SGD with momentum and layer-wise adaptive rate scaling (You et al., "Large Batch
+/// Training of Convolutional Networks").
+///
+/// The weights' step is scaled by the trust ratio
+/// `trust_coefficient * ||w|| / (||g|| + weight_decay * ||w||)`. The biases take plain
+/// momentum steps at the base learning rate, without weight decay.
+pub struct Lars {
+    learning_rate: Dtype,
+    momentum: Dtype,
+    weight_decay: Dtype,
+    trust_coefficient: Dtype,
+    epsilon: Dtype,
+
+    velocity_w: Matrix,
+    velocity_b: Matrix,
+}
+
+impl Lars {
+    pub fn new(
+        learning_rate: Dtype,
+        momentum: Dtype,          // Typically 0.9
+        weight_decay: Dtype,      // Typically 1e-4 to 5e-4
+        trust_coefficient: Dtype, // Typically 0.001
+        epsilon: Dtype,
+        input_size: usize,
+        output_size: usize,
+    ) -> Lars {
+        Lars {
+            learning_rate,
+            momentum,
+            weight_decay,
+            trust_coefficient,
+            epsilon,
+
+            velocity_w: Matrix::new(output_size, input_size),
+            velocity_b: Matrix::new(output_size, 1),
+        }
+    }
+}
+
+/// L2 norm of all elements of the matrix.
+pub(crate) fn norm(matrix: &Matrix) -> Dtype {
+    matrix.data.iter().map(|v| v * v).sum::<Dtype>().sqrt()
+}
+
+impl Optimizer for Lars {
+    fn update(
+        &mut self,
+        weights: &Matrix,
+        biases: &Matrix,
+        weights_gradients: Matrix,
+        bias_gradients: Matrix,
+    ) -> (Matrix, Matrix) {
+        // --- 1. Layer-wise trust ratio for the weights ---
+        let w_norm = norm(weights);
+        let g_norm = norm(&weights_gradients);
+        let trust_ratio = if w_norm > 0.0 && g_norm > 0.0 {
+            self.trust_coefficient * w_norm / (g_norm + self.weight_decay * w_norm + self.epsilon)
+        } else {
+            1.0
+        };
+
+        // --- 2. Momentum on the scaled, decayed gradient ---
+        // v = momentum * v + lr * trust_ratio * (g + wd * w)
+        let decayed_w = &weights_gradients + &(weights * self.weight_decay);
+        let local_lr = self.learning_rate * trust_ratio;
+        self.velocity_w = (&self.velocity_w * self.momentum) + (decayed_w * local_lr);
+        self.velocity_b =
+            (&self.velocity_b * self.momentum) + (bias_gradients * self.learning_rate);
+
+        (weights - &self.velocity_w, biases - &self.velocity_b)
+    }
+
+    fn learning_rate(&self) -> Dtype {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: Dtype) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state(&self) -> OptimizerState {
+        OptimizerState::new("Lars")
+            .with_scalar("learning_rate", self.learning_rate as f64)
+            .with_tensor("velocity_w", &self.velocity_w)
+            .with_tensor("velocity_b", &self.velocity_b)
+    }
+
+    fn load_state(&mut self, mut state: OptimizerState) -> anyhow::Result<()> {
+        state.expect_kind("Lars")?;
+        self.learning_rate = state.scalar("learning_rate")? as Dtype;
+        self.velocity_w = state.take_tensor("velocity_w", &self.velocity_w)?;
+        self.velocity_b = state.take_tensor("velocity_b", &self.velocity_b)?;
+        Ok(())
+    }
+}
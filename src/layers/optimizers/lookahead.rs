@@ -0,0 +1,124 @@
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::optimizers::{Optimizer, state::OptimizerState},
+};
+
+/// Lookahead (Zhang et al., "Lookahead Optimizer: k steps forward, 1 step back").
+///
+/// The wrapped optimizer updates the "fast" weights. Every `k` updates the "slow" weights move
+/// `alpha` of the way towards the fast weights, and the fast weights are reset to them.
+pub struct Lookahead {
+    inner: Box<dyn Optimizer>,
+    k: usize,
+    alpha: Dtype,
+
+    input_size: usize,
+    output_size: usize,
+
+    counter: usize,
+    // Initialized from the parameters seen by the first update.
+    slow_w: Option<Matrix>,
+    slow_b: Option<Matrix>,
+}
+
+impl Lookahead {
+    pub fn new(
+        inner: Box<dyn Optimizer>,
+        k: usize,
+        alpha: Dtype,
+        input_size: usize,
+        output_size: usize,
+    ) -> Lookahead {
+        assert!(k > 0, "k must be positive");
+        assert!(
+            (0.0..=1.0).contains(&alpha),
+            "alpha must be in [0, 1], got {}",
+            alpha
+        );
+
+        Lookahead {
+            inner,
+            k,
+            alpha,
+            input_size,
+            output_size,
+            counter: 0,
+            slow_w: None,
+            slow_b: None,
+        }
+    }
+}
+
+impl Optimizer for Lookahead {
+    fn update(
+        &mut self,
+        weights: &Matrix,
+        biases: &Matrix,
+        weights_gradients: Matrix,
+        bias_gradients: Matrix,
+    ) -> (Matrix, Matrix) {
+        let slow_w = self.slow_w.get_or_insert_with(|| weights.clone());
+        let slow_b = self.slow_b.get_or_insert_with(|| biases.clone());
+
+        let (fast_w, fast_b) =
+            self.inner
+                .update(weights, biases, weights_gradients, bias_gradients);
+
+        self.counter += 1;
+        if !self.counter.is_multiple_of(self.k) {
+            return (fast_w, fast_b);
+        }
+
+        // slow <- slow + alpha * (fast - slow), then fast <- slow
+        *slow_w = &*slow_w + &((&fast_w - &*slow_w) * self.alpha);
+        *slow_b = &*slow_b + &((&fast_b - &*slow_b) * self.alpha);
+
+        (slow_w.clone(), slow_b.clone())
+    }
+
+    fn learning_rate(&self) -> Dtype {
+        self.inner.learning_rate()
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: Dtype) {
+        self.inner.set_learning_rate(learning_rate);
+    }
+
+    fn state(&self) -> OptimizerState {
+        let mut state =
+            OptimizerState::new("Lookahead").with_scalar("counter", self.counter as f64);
+        if let (Some(slow_w), Some(slow_b)) = (&self.slow_w, &self.slow_b) {
+            state = state
+                .with_tensor("slow_w", slow_w)
+                .with_tensor("slow_b", slow_b);
+        }
+        state.inner = Some(Box::new(self.inner.state()));
+        state
+    }
+
+    fn load_state(&mut self, mut state: OptimizerState) -> anyhow::Result<()> {
+        state.expect_kind("Lookahead")?;
+        let counter = state.scalar("counter")? as usize;
+        // The slow weights only exist once the first update has happened.
+        let (slow_w, slow_b) = if state.tensors.is_empty() {
+            (None, None)
+        } else {
+            let weights = Matrix::new(self.output_size, self.input_size);
+            let biases = Matrix::new(self.output_size, 1);
+            (
+                Some(state.take_tensor("slow_w", &weights)?),
+                Some(state.take_tensor("slow_b", &biases)?),
+            )
+        };
+
+        let inner = state
+            .inner
+            .ok_or_else(|| anyhow::anyhow!("Lookahead state is missing the inner optimizer"))?;
+        self.inner.load_state(*inner)?;
+        self.counter = counter;
+        self.slow_w = slow_w;
+        self.slow_b = slow_b;
+        Ok(())
+    }
+}
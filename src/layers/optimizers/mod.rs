@@ -1,8 +1,25 @@
-use crate::data_structures::matrix::Matrix;
+use crate::{Dtype, data_structures::matrix::Matrix, layers::optimizers::state::OptimizerState};
 
 pub mod adagrad;
 pub mod adam;
+pub mod adamw;
+pub mod lamb;
+pub mod lars;
+pub mod lookahead;
+pub mod optimizer_tests;
+pub mod state;
 
 pub trait Optimizer {
     fn update(&mut self, weights: &Matrix, biases: &Matrix, gradients: Matrix, bias_gradients: Matrix) -> (Matrix, Matrix);
-}
\ No newline at end of file
+
+    fn learning_rate(&self) -> Dtype;
+
+    /// Overrides the learning rate used by subsequent updates (e.g. by a scheduler).
+    fn set_learning_rate(&mut self, learning_rate: Dtype);
+
+    /// Snapshot of the internal state, including the learning rate.
+    fn state(&self) -> OptimizerState;
+
+    /// Restores a snapshot taken by `state` on an optimizer of the same kind and shape.
+    fn load_state(&mut self, state: OptimizerState) -> anyhow::Result<()>;
+}
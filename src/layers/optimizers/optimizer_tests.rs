@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Dtype,
+        data_structures::matrix::Matrix,
+        layers::optimizers::{
+            Optimizer, adagrad::AdaGrad, adam::Adam, adamw::AdamW, lamb::Lamb, lars::Lars,
+            lookahead::Lookahead,
+        },
+    };
+
+    fn filled(rows: usize, cols: usize, value: Dtype) -> Matrix {
+        let mut m = Matrix::new(rows, cols);
+        m.data.iter_mut().for_each(|v| *v = value);
+        m
+    }
+
+    fn from_rows(rows: &[&[Dtype]]) -> Matrix {
+        let mut m = Matrix::new(rows.len(), rows[0].len());
+        for (r, row) in rows.iter().enumerate() {
+            for (c, val) in row.iter().enumerate() {
+                m.set(r, c, *val);
+            }
+        }
+        m
+    }
+
+    /// Parameters and gradients shared by the LARS / LAMB reference tests.
+    fn reference_problem() -> (Matrix, Matrix, Matrix, Matrix) {
+        (
+            from_rows(&[&[0.5, -0.25], &[1.0, 0.75]]),
+            from_rows(&[&[0.1], &[-0.2]]),
+            from_rows(&[&[0.2, -0.1], &[0.05, 0.3]]),
+            from_rows(&[&[0.01], &[-0.02]]),
+        )
+    }
+
+    fn assert_close(a: &Matrix, b: &Matrix) {
+        for (x, y) in a.data.iter().zip(b.data.iter()) {
+            assert!((x - y).abs() < 1e-6, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_adamw_decouples_weight_decay() {
+        let mut opt = AdamW::new(0.1, 0.9, 0.999, 1e-8, 0.5, 2, 2);
+        let weights = filled(2, 2, 2.0);
+        let biases = filled(2, 1, 2.0);
+
+        // Zero gradients: only the decay moves the weights, biases stay put.
+        let (w, b) = opt.update(&weights, &biases, Matrix::new(2, 2), Matrix::new(2, 1));
+        assert_close(&w, &filled(2, 2, 2.0 * (1.0 - 0.1 * 0.5)));
+        assert_close(&b, &biases);
+    }
+
+    #[test]
+    fn test_adamw_decay_opt_outs() {
+        let weights = filled(2, 2, 2.0);
+        let biases = filled(2, 1, 2.0);
+
+        let mut opt = AdamW::new(0.1, 0.9, 0.999, 1e-8, 0.5, 2, 2)
+            .decay_weights(false)
+            .decay_biases(true);
+        let (w, b) = opt.update(&weights, &biases, Matrix::new(2, 2), Matrix::new(2, 1));
+        assert_close(&w, &weights);
+        assert_close(&b, &filled(2, 1, 2.0 * (1.0 - 0.1 * 0.5)));
+    }
+
+    #[test]
+    fn test_adamw_without_decay_matches_adam() {
+        let weights = filled(2, 3, 0.5);
+        let biases = filled(2, 1, -0.5);
+        let grad_w = filled(2, 3, 0.25);
+        let grad_b = filled(2, 1, -0.1);
+
+        let mut adam = Adam::new(0.01, 0.9, 0.999, 1e-8, 3, 2);
+        let mut adamw = AdamW::new(0.01, 0.9, 0.999, 1e-8, 0.0, 3, 2);
+
+        let (w1, b1) = adam.update(&weights, &biases, grad_w.clone(), grad_b.clone());
+        let (w2, b2) = adamw.update(&weights, &biases, grad_w, grad_b);
+        assert_close(&w1, &w2);
+        assert_close(&b1, &b2);
+    }
+
+    #[test]
+    fn test_adagrad_momentum_accumulates_steps() {
+        let mut opt = AdaGrad::new(0.1, 0.0, 0.9, 2, 2);
+        let (mut w, mut b) = (filled(2, 2, 1.0), filled(2, 1, 1.0));
+        for _ in 0..2 {
+            (w, b) = opt.update(&w, &b, filled(2, 2, 1.0), filled(2, 1, 1.0));
+        }
+
+        // Step sizes are 0.1 / sqrt(1) and then 0.9 * 0.1 + 0.1 / sqrt(2).
+        let expected = 1.0 - 0.1 - (0.9 * 0.1 + 0.1 / (2.0 as Dtype).sqrt());
+        assert_close(&w, &filled(2, 2, expected));
+        assert_close(&b, &filled(2, 1, expected));
+    }
+
+    #[test]
+    fn test_lookahead_interpolates_every_k_steps() {
+        let weights = filled(2, 3, 0.5);
+        let biases = filled(2, 1, -0.5);
+        let grad_w = filled(2, 3, 0.25);
+        let grad_b = filled(2, 1, -0.1);
+
+        let mut fast = Adam::new(0.01, 0.9, 0.999, 1e-8, 3, 2);
+        let mut lookahead = Lookahead::new(
+            Box::new(Adam::new(0.01, 0.9, 0.999, 1e-8, 3, 2)),
+            2,
+            0.5,
+            3,
+            2,
+        );
+
+        // First step: identical to the inner optimizer.
+        let (fw1, fb1) = fast.update(&weights, &biases, grad_w.clone(), grad_b.clone());
+        let (lw1, lb1) = lookahead.update(&weights, &biases, grad_w.clone(), grad_b.clone());
+        assert_close(&fw1, &lw1);
+        assert_close(&fb1, &lb1);
+
+        // Second step: halfway between the starting point and the fast weights.
+        let (fw2, fb2) = fast.update(&fw1, &fb1, grad_w.clone(), grad_b.clone());
+        let (lw2, lb2) = lookahead.update(&lw1, &lb1, grad_w, grad_b);
+        assert_close(&lw2, &(&(&weights + &fw2) * 0.5));
+        assert_close(&lb2, &(&(&biases + &fb2) * 0.5));
+
+        lookahead.set_learning_rate(0.5);
+        assert_eq!(lookahead.learning_rate(), 0.5);
+    }
+
+    fn run_two_steps(opt: &mut dyn Optimizer) -> (Matrix, Matrix) {
+        let (mut w, mut b, grad_w, grad_b) = reference_problem();
+        for _ in 0..2 {
+            (w, b) = opt.update(&w, &b, grad_w.clone(), grad_b.clone());
+        }
+        (w, b)
+    }
+
+    // The LARS and LAMB reference values are printed by this float64 Python script, which
+    // runs two steps on `reference_problem` with the parameters of the tests:
+    //
+    // from math import sqrt
+    //
+    // w0, b0 = [0.5, -0.25, 1.0, 0.75], [0.1, -0.2]  # row-major
+    // gw, gb = [0.2, -0.1, 0.05, 0.3], [0.01, -0.02]
+    // norm = lambda x: sqrt(sum(v * v for v in x))
+    //
+    // def lars(lr=0.1, mom=0.9, wd=0.01, tc=0.02):
+    //     w, b, vw, vb = w0, b0, [0.0] * 4, [0.0] * 2
+    //     for _ in range(2):
+    //         ratio = tc * norm(w) / (norm(gw) + wd * norm(w))
+    //         vw = [mom * v + lr * ratio * (g + wd * x) for v, g, x in zip(vw, gw, w)]
+    //         vb = [mom * v + lr * g for v, g in zip(vb, gb)]
+    //         w, b = [x - v for x, v in zip(w, vw)], [x - v for x, v in zip(b, vb)]
+    //     return w, b
+    //
+    // def lamb(lr=0.01, b1=0.9, b2=0.999, eps=1e-6, wd=0.01):
+    //     w, b, m, v = w0, b0, [0.0] * 6, [0.0] * 6
+    //     for t in (1, 2):
+    //         g = gw + gb
+    //         m = [b1 * x + (1 - b1) * y for x, y in zip(m, g)]
+    //         v = [b2 * x + (1 - b2) * y * y for x, y in zip(v, g)]
+    //         r = [x / (1 - b1**t) / (sqrt(y / (1 - b2**t)) + eps) for x, y in zip(m, v)]
+    //         uw = [x + wd * y for x, y in zip(r[:4], w)]
+    //         w = [x - lr * norm(w) / norm(uw) * u for x, u in zip(w, uw)]
+    //         b = [x - lr * u for x, u in zip(b, r[4:])]
+    //     return w, b
+    //
+    // print(lars())
+    // print(lamb())
+
+    #[test]
+    fn test_lars_reference() {
+        let mut opt = Lars::new(0.1, 0.9, 0.01, 0.02, 0.0, 2, 2);
+        let (w, b) = run_two_steps(&mut opt);
+
+        assert_close(
+            &w,
+            &from_rows(&[&[0.4958403, -0.2479201], &[0.9987825, 0.7437604]]),
+        );
+        assert_close(&b, &from_rows(&[&[0.0971], &[-0.1942]]));
+    }
+
+    #[test]
+    fn test_lamb_reference() {
+        let mut opt = Lamb::new(0.01, 0.9, 0.999, 1e-6, 0.01, 2, 2);
+        let (w, b) = run_two_steps(&mut opt);
+
+        assert_close(
+            &w,
+            &from_rows(&[&[0.4863864, -0.2364203], &[0.9863189, 0.7363525]]),
+        );
+        assert_close(&b, &from_rows(&[&[0.080002], &[-0.180001]]));
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::data_structures::matrix::Matrix;
+
+/// Internal state of an optimizer (moments, accumulators, step counters), stored in
+/// training checkpoints so that training can resume exactly where it stopped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OptimizerState {
+    /// Name of the optimizer that produced the state, checked when loading.
+    pub kind: String,
+    /// Scalars such as the learning rate or the time step. Stored as `f64` so that step
+    /// counters stay exact.
+    pub scalars: BTreeMap<String, f64>,
+    pub tensors: BTreeMap<String, Matrix>,
+    /// State of a wrapped optimizer (e.g. the inner optimizer of `Lookahead`).
+    pub inner: Option<Box<OptimizerState>>,
+}
+
+impl OptimizerState {
+    pub fn new(kind: &str) -> Self {
+        OptimizerState {
+            kind: kind.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_scalar(mut self, name: &str, value: f64) -> Self {
+        self.scalars.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn with_tensor(mut self, name: &str, value: &Matrix) -> Self {
+        self.tensors.insert(name.to_string(), value.clone());
+        self
+    }
+
+    /// Fails unless the state was produced by an optimizer of the given kind.
+    pub fn expect_kind(&self, kind: &str) -> anyhow::Result<()> {
+        if self.kind != kind {
+            return Err(anyhow!(
+                "optimizer state is for '{}', cannot load it into '{}'",
+                self.kind,
+                kind
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn scalar(&self, name: &str) -> anyhow::Result<f64> {
+        self.scalars
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow!("{} state is missing scalar '{}'", self.kind, name))
+    }
+
+    /// Removes a tensor, checking that it has the same shape as `like`.
+    pub fn take_tensor(&mut self, name: &str, like: &Matrix) -> anyhow::Result<Matrix> {
+        let tensor = self
+            .tensors
+            .remove(name)
+            .ok_or_else(|| anyhow!("{} state is missing tensor '{}'", self.kind, name))?;
+        if (tensor.rows, tensor.cols) != (like.rows, like.cols)
+            || tensor.data.len() != like.data.len()
+        {
+            return Err(anyhow!(
+                "{} state tensor '{}' has shape ({}, {}), expected ({}, {})",
+                self.kind,
+                name,
+                tensor.rows,
+                tensor.cols,
+                like.rows,
+                like.cols
+            ));
+        }
+        Ok(tensor)
+    }
+}
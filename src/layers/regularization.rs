@@ -0,0 +1,106 @@
+use crate::{Dtype, data_structures::matrix::Matrix};
+
+/// Constraint projected onto a layer's weights after every optimizer step.
+/// Norms are taken per output unit, i.e. over each row of the `(output_size, input_size)` weights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightConstraint {
+    /// Rescales rows whose L2 norm exceeds the given value down to it.
+    MaxNorm(Dtype),
+    /// Rescales every row to unit L2 norm.
+    UnitNorm,
+}
+
+/// Per-layer weight regularization: L1 and L2 penalties (both together give elastic net)
+/// plus an optional constraint. Biases are never regularized.
+///
+/// The penalty is `l1 * sum(|w|) + l2 / 2 * sum(w^2)` with gradient `l1 * sign(w) + l2 * w`,
+/// i.e. `l2` is the classic coupled weight decay. `ConfigDenseLayer::weight_decay` and the
+/// `weight_decay` of an `adam` or `adagrad` `OptimizerSpec` set `l2`; decoupled decay (AdamW)
+/// and the layer-wise optimizers (LAMB, LARS) keep their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Regularizer {
+    pub l1: Dtype,
+    pub l2: Dtype,
+    pub constraint: Option<WeightConstraint>,
+}
+
+impl Regularizer {
+    pub fn l1(l1: Dtype) -> Self {
+        Regularizer {
+            l1,
+            ..Default::default()
+        }
+    }
+
+    pub fn l2(l2: Dtype) -> Self {
+        Regularizer {
+            l2,
+            ..Default::default()
+        }
+    }
+
+    pub fn elastic_net(l1: Dtype, l2: Dtype) -> Self {
+        Regularizer {
+            l1,
+            l2,
+            constraint: None,
+        }
+    }
+
+    pub fn with_constraint(mut self, constraint: WeightConstraint) -> Self {
+        self.constraint = Some(constraint);
+        self
+    }
+
+    /// Penalty term added to the loss.
+    pub fn penalty(&self, weights: &Matrix) -> Dtype {
+        let l1: Dtype = weights.data.iter().map(|w| w.abs()).sum();
+        let l2: Dtype = weights.data.iter().map(|w| w * w).sum();
+        self.l1 * l1 + 0.5 * self.l2 * l2
+    }
+
+    /// Adds the gradient of the penalty to `gradient`.
+    pub fn add_gradient(&self, weights: &Matrix, gradient: &mut Matrix) {
+        if self.l1 == 0.0 && self.l2 == 0.0 {
+            return;
+        }
+
+        for (g, w) in gradient.data.iter_mut().zip(weights.data.iter()) {
+            // sign(0) = 0 keeps zero weights at zero
+            let sign = if *w > 0.0 {
+                1.0
+            } else if *w < 0.0 {
+                -1.0
+            } else {
+                0.0
+            };
+            *g += self.l1 * sign + self.l2 * w;
+        }
+    }
+
+    /// Projects the weights onto the constraint, if any.
+    pub fn apply_constraint(&self, weights: &mut Matrix) {
+        let Some(constraint) = self.constraint else {
+            return;
+        };
+
+        for row in 0..weights.rows {
+            let norm = (0..weights.cols)
+                .map(|col| weights.get(row, col).powi(2))
+                .sum::<Dtype>()
+                .sqrt();
+
+            let scale = match constraint {
+                WeightConstraint::MaxNorm(max_norm) if norm > max_norm => max_norm / norm,
+                WeightConstraint::MaxNorm(_) => continue,
+                WeightConstraint::UnitNorm if norm > 0.0 => 1.0 / norm,
+                WeightConstraint::UnitNorm => continue,
+            };
+
+            for col in 0..weights.cols {
+                let val = weights.get(row, col);
+                weights.set(row, col, val * scale);
+            }
+        }
+    }
+}
@@ -2,17 +2,25 @@ use crate::{data_structures::matrix::Matrix, layers::Layer};
 
 pub struct ReLULayer {
     // Cache the input (Z) from the forward pass for use in the backward pass.
+    #[cfg(feature = "train")]
     input_cache: Matrix,
 }
 
 impl ReLULayer {
     pub fn new() -> ReLULayer {
         ReLULayer {
+            #[cfg(feature = "train")]
             input_cache: Matrix::new(0, 0),
         }
     }
 }
 
+impl Default for ReLULayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Layer for ReLULayer {
     fn get_weights(&self) -> Option<&Matrix> {
         None
@@ -25,7 +33,10 @@ impl Layer for ReLULayer {
     /// input: Matrix of shape (features, batch_size)
     /// output: Matrix of shape (features, batch_size)
     fn forward(&mut self, input: &Matrix) -> Matrix {
-        self.input_cache = input.clone(); // Cache input (Z)
+        #[cfg(feature = "train")]
+        {
+            self.input_cache = input.clone(); // Cache input (Z)
+        }
 
         let mut output = input.clone();
         for val in output.data.iter_mut() {
@@ -36,6 +47,7 @@ impl Layer for ReLULayer {
 
     /// Backward pass: dL/dX = dL/dY * ReLU'(X)
     /// ReLU'(x) is 1 if x > 0, and 0 otherwise.
+    #[cfg(feature = "train")]
     fn backward(&mut self, output_gradient: &Matrix) -> Matrix {
         // The learning_rate is ignored as activation layers have no trainable parameters.
 
@@ -1,19 +1,27 @@
-use crate::{Dtype, data_structures::matrix::Matrix, layers::Layer};
+use crate::{Dtype, data_structures::matrix::Matrix, layers::Layer, math};
 
 /// The Softmax activation layer (typically used as the output layer for classification).
 pub struct Softmax {
     // Cache the output of the forward pass for use in the backward pass.
+    #[cfg(feature = "train")]
     output_cache: Matrix,
 }
 
 impl Softmax {
     pub fn new() -> Softmax {
         Softmax {
+            #[cfg(feature = "train")]
             output_cache: Matrix::new(0, 0),
         }
     }
 }
 
+impl Default for Softmax {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Layer for Softmax {
     fn get_weights(&self) -> Option<&Matrix> {
         None
@@ -38,7 +46,7 @@ impl Layer for Softmax {
             // Calculate exponentials and sum
             let mut sum_exp = 0.0;
             for row in 0..input.rows {
-                let exp_val = (input.get(row, col) - max_val).exp();
+                let exp_val = math::exp(input.get(row, col) - max_val);
                 output.set(row, col, exp_val);
                 sum_exp += exp_val;
             }
@@ -51,7 +59,10 @@ impl Layer for Softmax {
 
         assert_eq!(output.rows, input.rows);
         assert_eq!(output.cols, input.cols);
-        self.output_cache = output.clone();
+        #[cfg(feature = "train")]
+        {
+            self.output_cache = output.clone();
+        }
 
         // sleep
         output
@@ -59,6 +70,7 @@ impl Layer for Softmax {
 
     /// Backward pass for Softmax combined with Categorical Cross-Entropy Loss
     /// dL/dX = Y_pred - Y_true
+    #[cfg(feature = "train")]
     fn backward(&mut self, target_true: &Matrix) -> Matrix {
         // The output_gradient is actually Y_true in this combined case
         // dL/dZ = Y_pred - Y_true
@@ -0,0 +1,79 @@
+//! Feed-forward neural networks on a column-major `Matrix`.
+//!
+//! The most used types are re-exported at the crate root: `Matrix`, the `Layer` trait with
+//! `DenseLayer`, `ReLULayer` and `Softmax`, and with `train` also `Network`, `History`,
+//! `ModelSpec`, `Optimizer`, `Callback` and `load_data`. Optimizers, callbacks, schedulers and
+//! model formats live in their modules. The experiment runners (Fashion MNIST, XOR, grid
+//! search) are part of the `neural-networks` binary, not of the library.
+//!
+//! ```
+//! # #[cfg(feature = "train")] {
+//! use neural_networks::{DenseLayer, Matrix, Network, ReLULayer, Softmax};
+//! use neural_networks::layers::dense::ConfigDenseLayer;
+//!
+//! let config = ConfigDenseLayer::default();
+//! let mut net = Network::new();
+//! net.add_layer(DenseLayer::new(4, 8, &config));
+//! net.add_layer(ReLULayer::new());
+//! net.add_layer(DenseLayer::new(8, 2, &config));
+//! net.add_layer(Softmax::new());
+//!
+//! let output = net.forward(&Matrix::new(4, 3));
+//! assert_eq!((output.rows, output.cols), (2, 3));
+//! # }
+//! ```
+//!
+//! With the default `train` feature the crate provides the full training stack. Built with
+//! `--no-default-features` it is `no_std` (using `alloc`) and only contains what is needed to
+//! run a trained model: `Matrix`, the `Layer` trait and the `DenseLayer`, `ReLULayer` and
+//! `Softmax` forward passes.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "train")]
+use anyhow::Context;
+#[cfg(feature = "train")]
+use rayon::ThreadPoolBuilder;
+
+#[cfg(feature = "train")]
+pub mod callbacks;
+#[cfg(feature = "train")]
+pub mod data;
+pub mod data_structures;
+#[cfg(feature = "train")]
+pub mod import;
+pub mod layers;
+pub mod math;
+pub mod math_tests;
+#[cfg(feature = "train")]
+pub mod networks;
+#[cfg(feature = "train")]
+pub mod onnx;
+#[cfg(feature = "train")]
+pub mod schedulers;
+
+pub use data_structures::matrix::Matrix;
+pub use layers::{Layer, dense::DenseLayer, relu::ReLULayer, softmax::Softmax};
+#[cfg(feature = "train")]
+pub use {
+    callbacks::Callback, data::load::load_data, layers::optimizers::Optimizer,
+    networks::{history::History, network::Network, spec::ModelSpec},
+};
+
+pub type Dtype = f32;
+
+pub const SEED: u64 = 42;
+
+/// Sets the size of rayon's global thread pool. Fails if the pool was already initialized.
+#[cfg(feature = "train")]
+pub fn initialize_rayon_pool(num_threads: usize) -> anyhow::Result<()> {
+    // This should only be called once, before any parallel operation.
+    ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build_global()
+        .context("failed to initialize the rayon thread pool")?;
+    log::info!("Rayon thread pool set to {} threads.", num_threads);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Floating point functions that are not part of `core`.
+
+use crate::Dtype;
+
+const LOG2_E: f32 = core::f32::consts::LOG2_E;
+// ln(2) split into a part with trailing zero bits and a remainder, so `k * LN2_HI` is exact.
+const LN2_HI: f32 = 0.693_145_75;
+const LN2_LO: f32 = 1.428_606_8e-6;
+
+/// `e^x`, from the standard library if it is linked.
+#[cfg(feature = "std")]
+pub fn exp(x: Dtype) -> Dtype {
+    x.exp()
+}
+
+/// `e^x`, from the standard library if it is linked.
+#[cfg(not(feature = "std"))]
+pub fn exp(x: Dtype) -> Dtype {
+    exp_polynomial(x)
+}
+
+// `exp_polynomial` works on the bits of an `f32`.
+#[cfg(not(feature = "std"))]
+const _: () = assert!(
+    core::mem::size_of::<Dtype>() == core::mem::size_of::<f32>(),
+    "the no_std `exp` needs Dtype = f32"
+);
+
+/// `e^x` without the standard library, accurate to a few ULPs.
+///
+/// Reduces `x = k * ln(2) + r` with `|r| <= ln(2) / 2`, evaluates a degree 7 Taylor
+/// polynomial for `e^r` and scales the result by `2^k` through the exponent bits.
+pub fn exp_polynomial(x: f32) -> f32 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 88.722_84 {
+        return f32::INFINITY;
+    }
+    if x < -103.972_08 {
+        return 0.0;
+    }
+
+    let k = (x * LOG2_E + if x < 0.0 { -0.5 } else { 0.5 }) as i32;
+    let r = (x - k as f32 * LN2_HI) - k as f32 * LN2_LO;
+
+    let mut p = 1.0 / 5040.0;
+    for coefficient in [1.0 / 720.0, 1.0 / 120.0, 1.0 / 24.0, 1.0 / 6.0, 0.5, 1.0, 1.0] {
+        p = p * r + coefficient;
+    }
+
+    // Results below the smallest normal number are scaled in two steps.
+    let pow2 = |k: i32| f32::from_bits(((k + 127) as u32) << 23);
+    if k < -126 {
+        p * pow2(k + 64) * pow2(-64)
+    } else {
+        p * pow2(k)
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests {
+    use crate::math::exp_polynomial;
+
+    #[test]
+    fn test_exp_polynomial_matches_std() {
+        for i in -1000..=880 {
+            let x = i as f32 / 10.0;
+            let (expected, actual) = (x.exp(), exp_polynomial(x));
+            let tolerance = expected * 4.0 * f32::EPSILON + f32::MIN_POSITIVE;
+            assert!(
+                (expected - actual).abs() <= tolerance,
+                "exp({}) = {}, got {}",
+                x,
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_exp_polynomial_special_values() {
+        assert_eq!(exp_polynomial(0.0), 1.0);
+        assert_eq!(exp_polynomial(100.0), f32::INFINITY);
+        assert_eq!(exp_polynomial(-200.0), 0.0);
+        assert!(exp_polynomial(f32::NAN).is_nan());
+    }
+}
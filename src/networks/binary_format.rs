@@ -0,0 +1,260 @@
+//! Compact little-endian binary model format.
+//!
+//! Layout (all integers little-endian):
+//!
+//! | offset | size        | content                                                     |
+//! |--------|-------------|-------------------------------------------------------------|
+//! | 0      | 8           | magic `NNMODEL\0`                                           |
+//! | 8      | 4           | format version                                              |
+//! | 12     | 4           | dtype: 1 = f32, 2 = f64                                     |
+//! | 16     | 4           | layer count `n`                                             |
+//! | 20     | 4           | reserved (0)                                                |
+//! | 24     | 32 * n      | layer table: kind, input size, output size, reserved (u32), |
+//! |        |             | weights offset, biases offset (u64, 0 if none)              |
+//! | ...    | ...         | raw `Matrix::data` blobs (column-major), 8-byte aligned     |
+//! | end-4  | 4           | CRC-32 of all preceding bytes                               |
+
+use std::{ops::Range, path::Path};
+
+use anyhow::{Context, anyhow};
+
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::{
+        Layer,
+        dense::ConfigDenseLayer,
+        relu::ReLULayer,
+        softmax::Softmax,
+    },
+    networks::{network::Network, serialization::LayerRecord},
+};
+
+pub const BINARY_MAGIC: [u8; 8] = *b"NNMODEL\0";
+pub const BINARY_FORMAT_VERSION: u32 = 1;
+
+const HEADER_SIZE: usize = 24;
+const TABLE_ENTRY_SIZE: usize = 32;
+const CHECKSUM_SIZE: usize = 4;
+const DTYPE_SIZE: usize = std::mem::size_of::<Dtype>();
+
+const KIND_DENSE: u32 = 0;
+const KIND_RELU: u32 = 1;
+const KIND_SOFTMAX: u32 = 2;
+
+fn dtype_code() -> u32 {
+    match DTYPE_SIZE {
+        4 => 1,
+        8 => 2,
+        _ => unreachable!("Dtype must be f32 or f64"),
+    }
+}
+
+fn align8(len: usize) -> usize {
+    len.div_ceil(8) * 8
+}
+
+/// Kind and location of one layer inside a binary model file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryLayer {
+    Dense {
+        input_size: usize,
+        output_size: usize,
+        // Byte ranges of the weights and biases inside the file.
+        weights: Range<usize>,
+        biases: Range<usize>,
+    },
+    ReLU,
+    Softmax,
+}
+
+/// Serializes the layers of a network into the binary format.
+pub fn encode(network: &Network) -> anyhow::Result<Vec<u8>> {
+    let records = network.layer_records()?;
+
+    let table_end = HEADER_SIZE + TABLE_ENTRY_SIZE * records.len();
+    let mut bytes = Vec::with_capacity(table_end);
+    bytes.extend_from_slice(&BINARY_MAGIC);
+    bytes.extend_from_slice(&BINARY_FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&dtype_code().to_le_bytes());
+    bytes.extend_from_slice(&(records.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+
+    // Blobs start after the table, every blob on an 8-byte boundary.
+    let mut blobs: Vec<&Matrix> = Vec::new();
+    let mut offset = align8(table_end);
+    let mut next_blob = |matrix: &'_ Matrix| {
+        let start = offset;
+        offset = align8(offset + matrix.data.len() * DTYPE_SIZE);
+        start as u64
+    };
+
+    for record in &records {
+        let (kind, input_size, output_size, weights_offset, biases_offset) = match record {
+            LayerRecord::Dense {
+                input_size,
+                output_size,
+                weights,
+                biases,
+            } => {
+                blobs.extend([weights, biases]);
+                let w = next_blob(weights);
+                let b = next_blob(biases);
+                (KIND_DENSE, *input_size, *output_size, w, b)
+            }
+            LayerRecord::ReLU => (KIND_RELU, 0, 0, 0, 0),
+            LayerRecord::Softmax => (KIND_SOFTMAX, 0, 0, 0, 0),
+        };
+        bytes.extend_from_slice(&kind.to_le_bytes());
+        bytes.extend_from_slice(&(input_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&(output_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&weights_offset.to_le_bytes());
+        bytes.extend_from_slice(&biases_offset.to_le_bytes());
+    }
+
+    for blob in blobs {
+        bytes.resize(align8(bytes.len()), 0);
+        for val in &blob.data {
+            bytes.extend_from_slice(&val.to_le_bytes());
+        }
+    }
+    bytes.resize(align8(bytes.len()), 0);
+
+    let checksum = crc32fast::hash(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    Ok(bytes)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// Validates the header, the checksum and the layer table, and returns the layer table.
+pub fn decode_table(bytes: &[u8]) -> anyhow::Result<Vec<BinaryLayer>> {
+    if bytes.len() < HEADER_SIZE + CHECKSUM_SIZE || bytes[..8] != BINARY_MAGIC {
+        return Err(anyhow!("not a binary model file (bad magic or too short)"));
+    }
+
+    let version = read_u32(bytes, 8);
+    if version != BINARY_FORMAT_VERSION {
+        return Err(anyhow!(
+            "unsupported binary model format version {} (this build reads version {})",
+            version,
+            BINARY_FORMAT_VERSION
+        ));
+    }
+
+    let payload_end = bytes.len() - CHECKSUM_SIZE;
+    let stored_checksum = read_u32(bytes, payload_end);
+    if crc32fast::hash(&bytes[..payload_end]) != stored_checksum {
+        return Err(anyhow!(
+            "checksum mismatch, the model file is truncated or corrupted"
+        ));
+    }
+
+    let dtype = read_u32(bytes, 12);
+    if dtype != dtype_code() {
+        return Err(anyhow!(
+            "model stores dtype {} but this build uses {}-byte floats",
+            dtype,
+            DTYPE_SIZE
+        ));
+    }
+
+    let layer_count = read_u32(bytes, 16) as usize;
+    if HEADER_SIZE + layer_count * TABLE_ENTRY_SIZE > payload_end {
+        return Err(anyhow!("layer table exceeds the file size"));
+    }
+
+    let blob = |offset: u64, len: usize| -> anyhow::Result<Range<usize>> {
+        let start = offset as usize;
+        let end = len
+            .checked_mul(DTYPE_SIZE)
+            .and_then(|size| size.checked_add(start))
+            .filter(|end| *end <= payload_end && start.is_multiple_of(8));
+        match end {
+            Some(end) => Ok(start..end),
+            None => Err(anyhow!("data blob at offset {} is out of bounds", offset)),
+        }
+    };
+
+    (0..layer_count)
+        .map(|i| {
+            let entry = HEADER_SIZE + i * TABLE_ENTRY_SIZE;
+            match read_u32(bytes, entry) {
+                KIND_DENSE => {
+                    let input_size = read_u32(bytes, entry + 4) as usize;
+                    let output_size = read_u32(bytes, entry + 8) as usize;
+                    Ok(BinaryLayer::Dense {
+                        input_size,
+                        output_size,
+                        weights: blob(
+                            read_u64(bytes, entry + 16),
+                            input_size.saturating_mul(output_size),
+                        )?,
+                        biases: blob(read_u64(bytes, entry + 24), output_size)?,
+                    })
+                }
+                KIND_RELU => Ok(BinaryLayer::ReLU),
+                KIND_SOFTMAX => Ok(BinaryLayer::Softmax),
+                kind => Err(anyhow!("unknown layer kind {} in layer {}", kind, i)),
+            }
+        })
+        .collect()
+}
+
+fn read_matrix(bytes: &[u8], range: Range<usize>, rows: usize, cols: usize) -> Matrix {
+    let data = bytes[range]
+        .chunks_exact(DTYPE_SIZE)
+        .map(|chunk| Dtype::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    Matrix { rows, cols, data }
+}
+
+/// Builds a network from a binary model, copying all weights. Dense layers get a fresh
+/// optimizer and the L2 penalty from `config`.
+pub fn decode(bytes: &[u8], config: &ConfigDenseLayer) -> anyhow::Result<Network> {
+    let mut network = Network::new();
+    for layer in decode_table(bytes)? {
+        let layer: Box<dyn Layer> = match layer {
+            BinaryLayer::Dense {
+                input_size,
+                output_size,
+                weights,
+                biases,
+            } => Box::new(config.build_layer(
+                read_matrix(bytes, weights, output_size, input_size),
+                read_matrix(bytes, biases, output_size, 1),
+            )),
+            BinaryLayer::ReLU => Box::new(ReLULayer::new()),
+            BinaryLayer::Softmax => Box::new(Softmax::new()),
+        };
+        network.layers.push(layer);
+    }
+    Ok(network)
+}
+
+impl Network {
+    /// Writes the architecture and all weights and biases in the compact binary format.
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, encode(self)?)
+            .with_context(|| format!("failed to write model to {}", path.display()))?;
+        log::info!("Saved binary model to {}", path.display());
+        Ok(())
+    }
+
+    /// Reads a network written by `save_binary`. Dense layers get a default `Adam` optimizer.
+    pub fn load_binary<P: AsRef<Path>>(path: P) -> anyhow::Result<Network> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read model from {}", path.display()))?;
+        decode(&bytes, &ConfigDenseLayer::default())
+            .with_context(|| format!("failed to load model from {}", path.display()))
+    }
+}
@@ -0,0 +1,289 @@
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::optimizers::state::OptimizerState,
+    networks::{
+        network::Network,
+        serialization::{LayerRecord, check_shape},
+    },
+};
+
+/// Identifies files written by `Network::save_checkpoint`.
+pub const CHECKPOINT_FORMAT: &str = "neural-networks/checkpoint";
+/// Bumped whenever the layout of `Checkpoint` changes incompatibly.
+pub const CHECKPOINT_FORMAT_VERSION: u32 = 1;
+
+/// Everything needed to continue training exactly where it stopped: parameters, optimizer
+/// state, training progress, the shuffling RNG and the EMA/SWA averages.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub format: String,
+    pub version: u32,
+    pub layers: Vec<LayerRecord>,
+    /// One entry per layer, `None` for layers without an optimizer.
+    pub optimizers: Vec<Option<OptimizerState>>,
+    pub completed_epochs: usize,
+    pub step_count: usize,
+    pub base_learning_rates: Option<Vec<Option<Dtype>>>,
+    pub shuffle_rng: Pcg64,
+    pub ema_shadow: Option<Vec<Matrix>>,
+    pub swa_average: Option<(Vec<Matrix>, usize)>,
+}
+
+impl Checkpoint {
+    pub fn from_network(network: &Network) -> anyhow::Result<Checkpoint> {
+        Ok(Checkpoint {
+            format: CHECKPOINT_FORMAT.to_string(),
+            version: CHECKPOINT_FORMAT_VERSION,
+            layers: network.layer_records()?,
+            optimizers: network
+                .layers
+                .iter()
+                .map(|layer| layer.optimizer().map(|optimizer| optimizer.state()))
+                .collect(),
+            completed_epochs: network.completed_epochs,
+            step_count: network.step_count,
+            base_learning_rates: network.base_learning_rates.clone(),
+            shuffle_rng: network.shuffle_rng.clone(),
+            ema_shadow: network.ema.as_ref().map(|ema| ema.shadow().to_vec()),
+            swa_average: network
+                .swa
+                .as_ref()
+                .map(|swa| (swa.average().to_vec(), swa.count())),
+        })
+    }
+
+    /// Parses a checkpoint, rejecting unknown formats and versions before reading the rest.
+    pub fn from_json(json: &str) -> anyhow::Result<Checkpoint> {
+        #[derive(Deserialize)]
+        struct Header {
+            format: Option<String>,
+            version: Option<u32>,
+        }
+
+        let header: Header = serde_json::from_str(json).context("checkpoint is not valid JSON")?;
+        match header.format.as_deref() {
+            Some(CHECKPOINT_FORMAT) => {}
+            Some(other) => return Err(anyhow!("unknown checkpoint format '{}'", other)),
+            None => return Err(anyhow!("missing checkpoint format header")),
+        }
+        match header.version {
+            Some(CHECKPOINT_FORMAT_VERSION) => {}
+            Some(other) => {
+                return Err(anyhow!(
+                    "unsupported checkpoint format version {} (this build reads version {})",
+                    other,
+                    CHECKPOINT_FORMAT_VERSION
+                ));
+            }
+            None => return Err(anyhow!("missing checkpoint format version")),
+        }
+
+        serde_json::from_str(json).context("malformed checkpoint")
+    }
+
+    /// Restores the checkpoint into a network with the same architecture and optimizers.
+    /// Fails if the layers or optimizers do not match those of the saved network, in which case
+    /// the network is left unchanged.
+    pub fn restore(self, network: &mut Network) -> anyhow::Result<()> {
+        if self.layers.len() != network.layers.len() || self.optimizers.len() != self.layers.len() {
+            return Err(anyhow!(
+                "checkpoint has {} layers, the network has {}",
+                self.layers.len(),
+                network.layers.len()
+            ));
+        }
+
+        // Check the architecture of every layer before overwriting any parameters.
+        let mut restored = Vec::with_capacity(self.layers.len());
+        for (i, ((record, state), layer)) in self
+            .layers
+            .into_iter()
+            .zip(self.optimizers)
+            .zip(network.layers.iter())
+            .enumerate()
+        {
+            let (expected, found) = (LayerRecord::from_layer(layer.as_ref())?, &record);
+            if std::mem::discriminant(&expected) != std::mem::discriminant(found) {
+                return Err(anyhow!(
+                    "layer {} is {}, the checkpoint has {}",
+                    i,
+                    layer_kind(&expected),
+                    layer_kind(found)
+                ));
+            }
+            if let (
+                LayerRecord::Dense {
+                    input_size,
+                    output_size,
+                    ..
+                },
+                LayerRecord::Dense {
+                    input_size: ci,
+                    output_size: co,
+                    weights,
+                    biases,
+                },
+            ) = (&expected, found)
+            {
+                if (input_size, output_size) != (ci, co) {
+                    return Err(anyhow!(
+                        "layer {} has shape ({}, {}), the checkpoint has ({}, {})",
+                        i,
+                        output_size,
+                        input_size,
+                        co,
+                        ci
+                    ));
+                }
+                check_shape("weights", weights, *co, *ci)
+                    .and_then(|_| check_shape("biases", biases, *co, 1))
+                    .with_context(|| format!("invalid parameters for layer {}", i))?;
+            }
+            if state.is_some() != layer.optimizer().is_some() {
+                return Err(anyhow!(
+                    "optimizer of layer {} does not match the checkpoint",
+                    i
+                ));
+            }
+            restored.push((record, state));
+        }
+
+        let shapes: Vec<(usize, usize)> = network
+            .layers
+            .iter_mut()
+            .flat_map(|layer| layer.parameters_mut())
+            .map(|parameter| (parameter.value.rows, parameter.value.cols))
+            .collect();
+        if let Some(shadow) = &self.ema_shadow {
+            check_averages("EMA shadow", shadow, &shapes)?;
+        }
+        if let Some((average, _)) = &self.swa_average {
+            check_averages("SWA average", average, &shapes)?;
+        }
+
+        // Optimizer states are only checked by loading them, so keep the current ones to put
+        // back if any of them is rejected.
+        let previous: Vec<Option<OptimizerState>> = network
+            .layers
+            .iter()
+            .map(|layer| layer.optimizer().map(|optimizer| optimizer.state()))
+            .collect();
+        let mut loaded = Ok(());
+        for (i, (layer, (_, state))) in network.layers.iter_mut().zip(&mut restored).enumerate() {
+            if let (Some(optimizer), Some(state)) = (layer.optimizer_mut(), state.take()) {
+                loaded = optimizer
+                    .load_state(state)
+                    .with_context(|| format!("invalid optimizer state for layer {}", i));
+                if loaded.is_err() {
+                    break;
+                }
+            }
+        }
+        if loaded.is_err() {
+            for (layer, state) in network.layers.iter_mut().zip(previous) {
+                if let (Some(optimizer), Some(state)) = (layer.optimizer_mut(), state) {
+                    optimizer
+                        .load_state(state)
+                        .expect("an optimizer accepts its own state");
+                }
+            }
+            return loaded;
+        }
+
+        // Everything is valid, nothing below can fail.
+        for (layer, (record, _)) in network.layers.iter_mut().zip(restored) {
+            if let LayerRecord::Dense {
+                weights, biases, ..
+            } = record
+            {
+                let mut parameters = layer.parameters_mut();
+                *parameters[0].value = weights;
+                *parameters[1].value = biases;
+            }
+        }
+        network.completed_epochs = self.completed_epochs;
+        network.step_count = self.step_count;
+        network.base_learning_rates = self.base_learning_rates;
+        network.shuffle_rng = self.shuffle_rng;
+        if let (Some(ema), Some(shadow)) = (network.ema.as_mut(), self.ema_shadow) {
+            ema.set_shadow(shadow);
+        }
+        if let (Some(swa), Some((average, count))) = (network.swa.as_mut(), self.swa_average) {
+            swa.restore(average, count);
+        }
+        Ok(())
+    }
+}
+
+/// Checks EMA/SWA averages against the parameter shapes. They are empty until the first update.
+fn check_averages(
+    name: &str,
+    averages: &[Matrix],
+    shapes: &[(usize, usize)],
+) -> anyhow::Result<()> {
+    if averages.is_empty() {
+        return Ok(());
+    }
+    if averages.len() != shapes.len() {
+        return Err(anyhow!(
+            "{} has {} matrices, the network has {} parameters",
+            name,
+            averages.len(),
+            shapes.len()
+        ));
+    }
+    for (i, (average, &(rows, cols))) in averages.iter().zip(shapes).enumerate() {
+        check_shape("values", average, rows, cols)
+            .with_context(|| format!("invalid {} for parameter {}", name, i))?;
+    }
+    Ok(())
+}
+
+fn layer_kind(record: &LayerRecord) -> &'static str {
+    match record {
+        LayerRecord::Dense { .. } => "Dense",
+        LayerRecord::ReLU => "ReLU",
+        LayerRecord::Softmax => "Softmax",
+    }
+}
+
+impl Network {
+    /// Writes a training checkpoint as JSON. Unlike `save`, this includes the optimizer state
+    /// and training progress, so `load_checkpoint` followed by `resume_training` resumes the run.
+    /// The state of callbacks is not saved.
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string(&Checkpoint::from_network(self)?)?;
+        // Write to a temporary file first so that an interrupted write keeps the old checkpoint.
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, json)
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .with_context(|| format!("failed to write checkpoint to {}", path.display()))?;
+        log::debug!("Saved checkpoint to {}", path.display());
+        Ok(())
+    }
+
+    /// Restores a checkpoint written by `save_checkpoint` into this network. The network must
+    /// be built with the same layers and optimizers (and EMA/SWA settings) as the saved one.
+    pub fn load_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read checkpoint from {}", path.display()))?;
+        Checkpoint::from_json(&json)
+            .and_then(|checkpoint| checkpoint.restore(self))
+            .with_context(|| format!("failed to load checkpoint from {}", path.display()))?;
+        log::info!(
+            "Loaded checkpoint from {} after epoch {}",
+            path.display(),
+            self.completed_epochs
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+//! Generates a standalone Rust source file that evaluates a trained network.
+//!
+//! The generated file only uses the standard library and contains the weights as `const` arrays and a
+//! `pub fn predict(input: &[f32; INPUT_SIZE]) -> [f32; OUTPUT_SIZE]` for a single sample.
+//! The arithmetic follows the order of `Network::forward`, so results match exactly.
+
+use std::{fmt::Write, path::Path};
+
+use anyhow::{Context, anyhow};
+
+use crate::{
+    data_structures::matrix::Matrix,
+    networks::{network::Network, serialization::LayerRecord},
+};
+
+const DENSE_FN: &str = "
+fn dense<const I: usize, const O: usize>(
+    input: &[f32; I],
+    weights: &[[f32; I]; O],
+    biases: &[f32; O],
+) -> [f32; O] {
+    let mut output = [0.0; O];
+    for ((out, row), bias) in output.iter_mut().zip(weights).zip(biases) {
+        let mut sum = 0.0;
+        for (w, x) in row.iter().zip(input) {
+            sum += w * x;
+        }
+        *out = sum + bias;
+    }
+    output
+}
+";
+
+const RELU_FN: &str = "
+fn relu<const N: usize>(mut x: [f32; N]) -> [f32; N] {
+    for v in x.iter_mut() {
+        if *v <= 0.0 {
+            *v = 0.0;
+        }
+    }
+    x
+}
+";
+
+const SOFTMAX_FN: &str = "
+fn softmax<const N: usize>(mut x: [f32; N]) -> [f32; N] {
+    let max = x.iter().fold(f32::NEG_INFINITY, |max, v| max.max(*v));
+    let mut sum = 0.0;
+    for v in x.iter_mut() {
+        *v = (*v - max).exp();
+        sum += *v;
+    }
+    for v in x.iter_mut() {
+        *v /= sum;
+    }
+    x
+}
+";
+
+fn float_literal(value: f32) -> anyhow::Result<String> {
+    if !value.is_finite() {
+        return Err(anyhow!(
+            "cannot generate code for non-finite weight {}",
+            value
+        ));
+    }
+    // `Debug` prints the shortest representation that parses back to the same value.
+    Ok(format!("{:?}", value))
+}
+
+/// Writes `(output_size, input_size)` weights as nested rows of `[[f32; input_size]; output_size]`.
+fn write_weights(source: &mut String, name: &str, weights: &Matrix) -> anyhow::Result<()> {
+    writeln!(
+        source,
+        "const {}: [[f32; {}]; {}] = [",
+        name, weights.cols, weights.rows
+    )?;
+    for r in 0..weights.rows {
+        let row = (0..weights.cols)
+            .map(|c| float_literal(weights.get(r, c)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        writeln!(source, "    [{}],", row.join(", "))?;
+    }
+    writeln!(source, "];")?;
+    Ok(())
+}
+
+fn write_biases(source: &mut String, name: &str, biases: &Matrix) -> anyhow::Result<()> {
+    let values = biases
+        .data
+        .iter()
+        .map(|v| float_literal(*v))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    writeln!(
+        source,
+        "const {}: [f32; {}] = [{}];",
+        name,
+        biases.rows,
+        values.join(", ")
+    )?;
+    Ok(())
+}
+
+/// Rust source code of a dependency-free `predict` function equivalent to `network.forward`.
+pub fn generate_rust(network: &Network) -> anyhow::Result<String> {
+    let records = network.layer_records()?;
+
+    // Track the feature count through the layers to check that the dense layers fit together.
+    let mut sizes: Option<(usize, usize)> = None;
+    let mut summary = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        match record {
+            LayerRecord::Dense {
+                input_size,
+                output_size,
+                ..
+            } => {
+                if let Some((_, current)) = sizes
+                    && current != *input_size
+                {
+                    return Err(anyhow!(
+                        "layer {} expects {} inputs, but the previous layer has {} outputs",
+                        i,
+                        input_size,
+                        current
+                    ));
+                }
+                let input = sizes.map_or(*input_size, |(input, _)| input);
+                sizes = Some((input, *output_size));
+                summary.push(format!("Dense({} -> {})", input_size, output_size));
+            }
+            LayerRecord::ReLU => summary.push("ReLU".to_string()),
+            LayerRecord::Softmax => summary.push("Softmax".to_string()),
+        }
+    }
+    let (input_size, output_size) =
+        sizes.ok_or_else(|| anyhow!("cannot generate code for a network without dense layers"))?;
+
+    let mut source = String::new();
+    writeln!(
+        source,
+        "// Generated by {} {}. Do not edit.",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(source, "// Layers: {}", summary.join(", "))?;
+    writeln!(source)?;
+    writeln!(source, "pub const INPUT_SIZE: usize = {};", input_size)?;
+    writeln!(source, "pub const OUTPUT_SIZE: usize = {};", output_size)?;
+
+    let mut body = Vec::new();
+    let (mut uses_relu, mut uses_softmax) = (false, false);
+    for (i, record) in records.iter().enumerate() {
+        match record {
+            LayerRecord::Dense {
+                weights, biases, ..
+            } => {
+                let (w_name, b_name) = (
+                    format!("LAYER_{}_WEIGHTS", i),
+                    format!("LAYER_{}_BIASES", i),
+                );
+                writeln!(source)?;
+                write_weights(&mut source, &w_name, weights)?;
+                write_biases(&mut source, &b_name, biases)?;
+                let input = if body.is_empty() { "input" } else { "&x" };
+                body.push(format!("dense({}, &{}, &{})", input, w_name, b_name));
+            }
+            LayerRecord::ReLU if !body.is_empty() => {
+                uses_relu = true;
+                body.push("relu(x)".to_string());
+            }
+            LayerRecord::Softmax if !body.is_empty() => {
+                uses_softmax = true;
+                body.push("softmax(x)".to_string());
+            }
+            _ => {
+                return Err(anyhow!(
+                    "layer {} comes before the first dense layer, which is not supported",
+                    i
+                ));
+            }
+        }
+    }
+
+    source.push_str(DENSE_FN);
+    if uses_relu {
+        source.push_str(RELU_FN);
+    }
+    if uses_softmax {
+        source.push_str(SOFTMAX_FN);
+    }
+    writeln!(source)?;
+    writeln!(
+        source,
+        "pub fn predict(input: &[f32; INPUT_SIZE]) -> [f32; OUTPUT_SIZE] {{"
+    )?;
+    let (last, rest) = body.split_last().unwrap();
+    for expression in rest {
+        writeln!(source, "    let x = {};", expression)?;
+    }
+    writeln!(source, "    {}", last)?;
+    writeln!(source, "}}")?;
+    Ok(source)
+}
+
+impl Network {
+    /// Writes a standalone Rust source file with a `predict` function, see `generate_rust`.
+    pub fn export_rust<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let source = generate_rust(self)?;
+        std::fs::write(path, source)
+            .with_context(|| format!("failed to write generated code to {}", path.display()))?;
+        log::info!("Generated inference code in {}", path.display());
+        Ok(())
+    }
+}
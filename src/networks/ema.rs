@@ -0,0 +1,65 @@
+use crate::{Dtype, data_structures::matrix::Matrix, layers::Layer};
+
+/// Exponential moving average (EMA) of all trainable parameters of a network.
+///
+/// After every optimizer step: `shadow = decay * shadow + (1 - decay) * value`.
+/// The shadow copy is only swapped into the layers for evaluation.
+pub struct ExponentialMovingAverage {
+    pub decay: Dtype,
+    // One matrix per parameter, in the order of `Layer::parameters_mut` over all layers.
+    shadow: Vec<Matrix>,
+}
+
+impl ExponentialMovingAverage {
+    pub fn new(decay: Dtype) -> Self {
+        assert!(
+            (0.0..1.0).contains(&decay),
+            "EMA decay must be in [0, 1), got {}",
+            decay
+        );
+        ExponentialMovingAverage {
+            decay,
+            shadow: Vec::new(),
+        }
+    }
+
+    /// Whether at least one update has been recorded.
+    pub fn is_initialized(&self) -> bool {
+        !self.shadow.is_empty()
+    }
+
+    /// The shadow copy, one matrix per parameter (empty before the first update).
+    pub fn shadow(&self) -> &[Matrix] {
+        &self.shadow
+    }
+
+    /// Replaces the shadow copy, e.g. when restoring a checkpoint.
+    pub fn set_shadow(&mut self, shadow: Vec<Matrix>) {
+        self.shadow = shadow;
+    }
+
+    /// Moves the shadow copy towards the current parameters.
+    /// The first call initializes the shadow copy with the parameters.
+    pub fn update(&mut self, layers: &mut [Box<dyn Layer>]) {
+        let parameters = layers.iter_mut().flat_map(|layer| layer.parameters_mut());
+
+        if !self.is_initialized() {
+            self.shadow = parameters.map(|parameter| parameter.value.clone()).collect();
+            return;
+        }
+
+        for (shadow, parameter) in self.shadow.iter_mut().zip(parameters) {
+            for (s, v) in shadow.data.iter_mut().zip(parameter.value.data.iter()) {
+                *s = self.decay * *s + (1.0 - self.decay) * v;
+            }
+        }
+    }
+
+    /// Exchanges the shadow copy with the layer parameters. Calling it twice restores both.
+    pub fn swap(&mut self, layers: &mut [Box<dyn Layer>]) {
+        let parameters = layers.iter_mut().flat_map(|layer| layer.parameters_mut());
+        for (shadow, parameter) in self.shadow.iter_mut().zip(parameters) {
+            std::mem::swap(shadow, parameter.value);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// Generated by neural-networks 0.1.0. Do not edit.
+// Layers: Dense(4 -> 5), ReLU, Dense(5 -> 3), Softmax
+
+pub const INPUT_SIZE: usize = 4;
+pub const OUTPUT_SIZE: usize = 3;
+
+const LAYER_0_WEIGHTS: [[f32; 4]; 5] = [
+    [-0.75, 0.375, -0.125, -0.625],
+    [0.125, -0.375, 0.75, 0.25],
+    [-0.625, 0.5, 0.0, -0.5],
+    [0.25, -0.25, -0.75, 0.375],
+    [-0.5, 0.625, 0.125, -0.375],
+];
+const LAYER_0_BIASES: [f32; 5] = [-0.375, 0.5, -0.25, 0.625, -0.125];
+
+const LAYER_2_WEIGHTS: [[f32; 5]; 3] = [
+    [-0.625, 0.375, -0.25, 0.75, 0.125],
+    [0.25, -0.375, 0.625, 0.0, -0.625],
+    [-0.5, 0.5, -0.125, -0.75, 0.25],
+];
+const LAYER_2_BIASES: [f32; 3] = [-0.125, 0.75, 0.0];
+
+fn dense<const I: usize, const O: usize>(
+    input: &[f32; I],
+    weights: &[[f32; I]; O],
+    biases: &[f32; O],
+) -> [f32; O] {
+    let mut output = [0.0; O];
+    for ((out, row), bias) in output.iter_mut().zip(weights).zip(biases) {
+        let mut sum = 0.0;
+        for (w, x) in row.iter().zip(input) {
+            sum += w * x;
+        }
+        *out = sum + bias;
+    }
+    output
+}
+
+fn relu<const N: usize>(mut x: [f32; N]) -> [f32; N] {
+    for v in x.iter_mut() {
+        if *v <= 0.0 {
+            *v = 0.0;
+        }
+    }
+    x
+}
+
+fn softmax<const N: usize>(mut x: [f32; N]) -> [f32; N] {
+    let max = x.iter().fold(f32::NEG_INFINITY, |max, v| max.max(*v));
+    let mut sum = 0.0;
+    for v in x.iter_mut() {
+        *v = (*v - max).exp();
+        sum += *v;
+    }
+    for v in x.iter_mut() {
+        *v /= sum;
+    }
+    x
+}
+
+pub fn predict(input: &[f32; INPUT_SIZE]) -> [f32; OUTPUT_SIZE] {
+    let x = dense(input, &LAYER_0_WEIGHTS, &LAYER_0_BIASES);
+    let x = relu(x);
+    let x = dense(&x, &LAYER_2_WEIGHTS, &LAYER_2_BIASES);
+    softmax(x)
+}
@@ -0,0 +1,68 @@
+use crate::{Dtype, data_structures::matrix::Matrix};
+
+/// Gradient clipping applied by `Network` between the backward pass and the optimizer step.
+/// Both limits are optional; value clipping runs before norm clipping when both are set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GradientClipping {
+    /// Clamp every gradient element into `[-clip_value, clip_value]`.
+    pub clip_value: Option<Dtype>,
+    /// Rescale all gradients of the network so that their global L2 norm is at most `max_norm`.
+    pub max_norm: Option<Dtype>,
+}
+
+impl GradientClipping {
+    pub fn by_value(clip_value: Dtype) -> Self {
+        GradientClipping {
+            clip_value: Some(clip_value),
+            max_norm: None,
+        }
+    }
+
+    pub fn by_global_norm(max_norm: Dtype) -> Self {
+        GradientClipping {
+            clip_value: None,
+            max_norm: Some(max_norm),
+        }
+    }
+
+    /// Clips the gradients in place and returns their global L2 norm before clipping.
+    pub fn apply(&self, gradients: &mut [&mut Matrix]) -> Dtype {
+        let norm = global_norm(gradients);
+
+        if let Some(clip_value) = self.clip_value {
+            for gradient in gradients.iter_mut() {
+                for val in gradient.data.iter_mut() {
+                    *val = val.clamp(-clip_value, clip_value);
+                }
+            }
+        }
+
+        if let Some(max_norm) = self.max_norm {
+            // Value clipping may already have shrunk the gradients.
+            let current_norm = match self.clip_value {
+                Some(_) => global_norm(gradients),
+                None => norm,
+            };
+            if current_norm > max_norm {
+                let scale = max_norm / (current_norm + 1e-6);
+                for gradient in gradients.iter_mut() {
+                    for val in gradient.data.iter_mut() {
+                        *val *= scale;
+                    }
+                }
+            }
+        }
+
+        norm
+    }
+}
+
+/// L2 norm of all gradients concatenated into a single vector.
+pub fn global_norm(gradients: &[&mut Matrix]) -> Dtype {
+    gradients
+        .iter()
+        .flat_map(|gradient| gradient.data.iter())
+        .map(|val| val * val)
+        .sum::<Dtype>()
+        .sqrt()
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Dtype;
+
+pub const LOSS: &str = "loss";
+pub const ACCURACY: &str = "accuracy";
+pub const VAL_LOSS: &str = "val_loss";
+pub const VAL_ACCURACY: &str = "val_accuracy";
+
+/// Named metrics of an epoch, shared by all callbacks. `Network::train` fills in `LOSS` and
+/// `ACCURACY`, and `VAL_LOSS` and `VAL_ACCURACY` if it was given validation data.
+pub type Logs = BTreeMap<String, Dtype>;
+
+/// Metrics of one training epoch. `loss` and `accuracy` are means over all training samples
+/// of the epoch, as seen by the batches during training; the validation metrics are computed
+/// after the epoch if validation data was given.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct TrainingMetric {
+    pub epoch: u32,
+    pub loss: Dtype,
+    pub accuracy: Dtype,
+    pub val_loss: Option<Dtype>,
+    pub val_accuracy: Option<Dtype>,
+}
+
+impl TrainingMetric {
+    /// The metrics as a `Logs` map, without the epoch.
+    pub fn to_logs(&self) -> Logs {
+        let mut logs = Logs::new();
+        logs.insert(LOSS.to_string(), self.loss);
+        logs.insert(ACCURACY.to_string(), self.accuracy);
+        if let Some(val_loss) = self.val_loss {
+            logs.insert(VAL_LOSS.to_string(), val_loss);
+        }
+        if let Some(val_accuracy) = self.val_accuracy {
+            logs.insert(VAL_ACCURACY.to_string(), val_accuracy);
+        }
+        logs
+    }
+
+    /// Reads the metrics of `epoch` back from `logs`; missing training metrics are NaN.
+    pub fn from_logs(epoch: u32, logs: &Logs) -> TrainingMetric {
+        TrainingMetric {
+            epoch,
+            loss: logs.get(LOSS).copied().unwrap_or(Dtype::NAN),
+            accuracy: logs.get(ACCURACY).copied().unwrap_or(Dtype::NAN),
+            val_loss: logs.get(VAL_LOSS).copied(),
+            val_accuracy: logs.get(VAL_ACCURACY).copied(),
+        }
+    }
+}
+
+/// Per-epoch metrics of a `Network::train` call, in epoch order.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct History {
+    pub epochs: Vec<TrainingMetric>,
+}
+
+impl History {
+    /// Metrics of the last completed epoch.
+    pub fn last(&self) -> Option<&TrainingMetric> {
+        self.epochs.last()
+    }
+
+    pub fn losses(&self) -> Vec<Dtype> {
+        self.epochs.iter().map(|m| m.loss).collect()
+    }
+
+    pub fn accuracies(&self) -> Vec<Dtype> {
+        self.epochs.iter().map(|m| m.accuracy).collect()
+    }
+
+    /// Validation losses of the epochs that were validated.
+    pub fn val_losses(&self) -> Vec<Dtype> {
+        self.epochs.iter().filter_map(|m| m.val_loss).collect()
+    }
+
+    pub fn val_accuracies(&self) -> Vec<Dtype> {
+        self.epochs.iter().filter_map(|m| m.val_accuracy).collect()
+    }
+
+    /// The epoch with the lowest validation loss, or training loss without validation data.
+    pub fn best_epoch(&self) -> Option<&TrainingMetric> {
+        self.epochs.iter().min_by(|a, b| {
+            let key = |m: &TrainingMetric| m.val_loss.unwrap_or(m.loss);
+            key(a).total_cmp(&key(b))
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use std::{fs::File, path::Path};
+
+use anyhow::{Context, anyhow};
+use memmap2::Mmap;
+
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::{Layer, dense::ConfigDenseLayer, relu::ReLULayer, softmax::Softmax},
+    networks::{
+        binary_format::{BinaryLayer, decode, decode_table},
+        network::Network,
+    },
+};
+
+/// Inference-only view of a binary model file whose weights stay in a read-only memory map
+/// instead of being copied into `Matrix` buffers.
+///
+/// The file must not be modified while it is mapped.
+pub struct MappedModel {
+    mmap: Mmap,
+    layers: Vec<BinaryLayer>,
+}
+
+impl MappedModel {
+    /// Maps a file written by `Network::save_binary` and validates its header and checksum.
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<MappedModel> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        if file.metadata()?.len() == 0 {
+            return Err(anyhow!("cannot map an empty file"));
+        }
+        // SAFETY: the mapping is read-only, and the type documents that the file must not be
+        // modified while it is mapped.
+        let mmap = unsafe { Mmap::map(&file) }.context("mmap failed")?;
+        let layers = decode_table(&mmap)
+            .with_context(|| format!("failed to load model from {}", path.display()))?;
+        Ok(MappedModel { mmap, layers })
+    }
+
+    pub fn layers(&self) -> &[BinaryLayer] {
+        &self.layers
+    }
+
+    /// Values of a blob inside the mapping, without copying.
+    fn values(&self, range: &std::ops::Range<usize>) -> &[Dtype] {
+        let bytes = &self.mmap[range.clone()];
+        // The mapping is page aligned and `decode_table` checked that blobs are 8-byte
+        // aligned, and the stored little-endian floats match the target's layout.
+        assert_eq!(bytes.as_ptr() as usize % std::mem::align_of::<Dtype>(), 0);
+        // SAFETY: alignment checked above, length is a multiple of the Dtype size.
+        unsafe {
+            std::slice::from_raw_parts(
+                bytes.as_ptr() as *const Dtype,
+                bytes.len() / std::mem::size_of::<Dtype>(),
+            )
+        }
+    }
+
+    /// Performs the forward pass, reading weights straight from the mapping.
+    /// input: Matrix of shape (features, batch_size)
+    pub fn forward(&self, input: &Matrix) -> Matrix {
+        let mut output = input.clone();
+        for layer in &self.layers {
+            output = match layer {
+                BinaryLayer::Dense {
+                    input_size,
+                    output_size,
+                    weights,
+                    biases,
+                } => {
+                    assert_eq!(output.rows, *input_size, "input size mismatch");
+                    let (w, b) = (self.values(weights), self.values(biases));
+
+                    // Weights are column-major (output_size, input_size), like `Matrix`.
+                    let mut result = Matrix::new(*output_size, output.cols);
+                    for col in 0..output.cols {
+                        for row in 0..*output_size {
+                            let mut sum = b[row];
+                            for k in 0..*input_size {
+                                sum += w[row + k * output_size] * output.get(k, col);
+                            }
+                            result.set(row, col, sum);
+                        }
+                    }
+                    result
+                }
+                BinaryLayer::ReLU => ReLULayer::new().forward(&output),
+                BinaryLayer::Softmax => Softmax::new().forward(&output),
+            };
+        }
+        output
+    }
+
+    /// Copies the weights into a trainable `Network`.
+    pub fn to_network(&self) -> anyhow::Result<Network> {
+        decode(&self.mmap, &ConfigDenseLayer::default())
+    }
+}
@@ -1 +1,15 @@
-pub mod network;
\ No newline at end of file
+pub mod binary_format;
+pub mod checkpoint;
+pub mod codegen;
+pub mod ema;
+pub mod gradient_clipping;
+pub mod history;
+#[cfg(target_endian = "little")]
+pub mod mapped_model;
+pub mod network;
+pub mod network_tests;
+pub mod serialization;
+pub mod spec;
+pub mod swa;
+#[cfg(test)]
+pub mod test_fixtures;
\ No newline at end of file
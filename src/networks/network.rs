@@ -1,18 +1,58 @@
-use indicatif::{ProgressBar, ProgressStyle};
-use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
-use crate::{Dtype, callbacks::Callback, data_structures::matrix::Matrix, layers::Layer};
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::{SeedableRng, seq::SliceRandom};
+use rand_pcg::Pcg64;
+
+use crate::{
+    Dtype, SEED,
+    callbacks::{
+        Callback,
+        training_context::{CallbackAction, TrainingContext},
+    },
+    data_structures::matrix::Matrix,
+    layers::{
+        Layer, forward_all,
+        optimizers::{Optimizer, lookahead::Lookahead},
+    },
+    networks::{
+        ema::ExponentialMovingAverage,
+        gradient_clipping::GradientClipping,
+        history::{ACCURACY, History, LOSS, Logs, TrainingMetric},
+        swa::StochasticWeightAveraging,
+    },
+    schedulers::{LrScheduler, ScheduleInterval},
+};
+
+/// Number of samples `validate` passes through the network at once by default.
+pub const DEFAULT_VALIDATION_BATCH_SIZE: usize = 1024;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
-pub struct TrainingMetric {
-    pub epoch: u32,
-    pub loss: Dtype,
-    pub accuracy: Dtype,
-}
 pub struct Network {
     pub(crate) layers: Vec<Box<dyn Layer>>,
     bar_style: ProgressStyle,
     callbacks: Vec<Box<dyn Callback>>,
+    lr_scheduler: Option<(Box<dyn LrScheduler>, ScheduleInterval)>,
+    gradient_clipping: GradientClipping,
+    last_gradient_norm: Option<Dtype>,
+    accumulation_steps: usize,
+    pub(crate) ema: Option<ExponentialMovingAverage>,
+    evaluate_with_ema: bool,
+    validation_batch_size: usize,
+    pub(crate) swa: Option<StochasticWeightAveraging>,
+    include_regularization_in_loss: bool,
+
+    // Training progress, saved in checkpoints so that `train` can resume.
+    pub(crate) completed_epochs: usize,
+    pub(crate) step_count: usize,
+    pub(crate) base_learning_rates: Option<Vec<Option<Dtype>>>,
+    pub(crate) shuffle_rng: Pcg64,
+    checkpoint_path: Option<PathBuf>,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Network {
@@ -24,6 +64,20 @@ impl Network {
             )
             .unwrap(),
             callbacks: Vec::new(),
+            lr_scheduler: None,
+            gradient_clipping: GradientClipping::default(),
+            last_gradient_norm: None,
+            accumulation_steps: 1,
+            ema: None,
+            evaluate_with_ema: false,
+            validation_batch_size: DEFAULT_VALIDATION_BATCH_SIZE,
+            swa: None,
+            include_regularization_in_loss: false,
+            completed_epochs: 0,
+            step_count: 0,
+            base_learning_rates: None,
+            shuffle_rng: Pcg64::seed_from_u64(SEED),
+            checkpoint_path: None,
         }
     }
 
@@ -31,31 +85,237 @@ impl Network {
         self.layers.push(Box::new(layer));
     }
 
+    /// Number of input features, taken from the first layer with weights.
+    pub fn input_size(&self) -> Option<usize> {
+        self.layers.iter().find_map(|l| l.get_weights()).map(|w| w.cols)
+    }
+
+    /// Number of outputs, taken from the last layer with weights.
+    pub fn output_size(&self) -> Option<usize> {
+        self.layers.iter().rev().find_map(|l| l.get_weights()).map(|w| w.rows)
+    }
+
     pub fn add_callback<C: Callback + 'static>(&mut self, callback: C) {
         self.callbacks.push(Box::<C>::new(callback));
     }
 
+    /// Drives the learning rate of every layer's optimizer during `train`.
+    pub fn set_lr_scheduler<S: LrScheduler + 'static>(
+        &mut self,
+        scheduler: S,
+        interval: ScheduleInterval,
+    ) {
+        self.lr_scheduler = Some((Box::new(scheduler), interval));
+    }
+
+    /// Copies of all trainable parameters, in the order of `Layer::parameters_mut` over all layers.
+    pub fn parameter_values(&mut self) -> Vec<Matrix> {
+        self.layers
+            .iter_mut()
+            .flat_map(|layer| layer.parameters_mut())
+            .map(|parameter| parameter.value.clone())
+            .collect()
+    }
+
+    /// Overwrites all trainable parameters with values taken by `parameter_values`.
+    pub fn set_parameter_values(&mut self, values: &[Matrix]) {
+        let parameters = self
+            .layers
+            .iter_mut()
+            .flat_map(|layer| layer.parameters_mut());
+        for (value, parameter) in values.iter().zip(parameters) {
+            parameter.value.data.copy_from_slice(&value.data);
+        }
+    }
+
+    /// Learning rate of every layer's optimizer, `None` for layers without parameters.
+    pub fn learning_rates(&mut self) -> Vec<Option<Dtype>> {
+        self.layers
+            .iter_mut()
+            .map(|layer| layer.optimizer_mut().map(|opt| opt.learning_rate()))
+            .collect()
+    }
+
+    /// Replaces the learning rate of every layer's optimizer with `f(current_lr)`.
+    /// Note that an active `LrScheduler` overwrites the learning rates on its next step.
+    pub fn update_learning_rates(&mut self, f: impl Fn(Dtype) -> Dtype) {
+        for layer in self.layers.iter_mut() {
+            if let Some(optimizer) = layer.optimizer_mut() {
+                optimizer.set_learning_rate(f(optimizer.learning_rate()));
+            }
+        }
+    }
+
+    /// Sets each optimizer's learning rate to the scheduled value for `step`.
+    fn apply_lr_schedule(&mut self, base_learning_rates: &[Option<Dtype>], step: usize) {
+        let Some((scheduler, _)) = &self.lr_scheduler else {
+            return;
+        };
+
+        for (layer, base_lr) in self.layers.iter_mut().zip(base_learning_rates) {
+            if let (Some(optimizer), Some(base_lr)) = (layer.optimizer_mut(), base_lr) {
+                optimizer.set_learning_rate(scheduler.learning_rate(*base_lr, step));
+            }
+        }
+    }
+
+    /// Sums the gradients of `steps` consecutive batches before each optimizer step,
+    /// giving an effective batch size of `steps * batch_size` in `train`.
+    pub fn set_accumulation_steps(&mut self, steps: usize) {
+        assert!(steps > 0, "accumulation steps must be positive");
+        self.accumulation_steps = steps;
+    }
+
+    /// Keeps an exponential moving average of all weights and biases, updated after every
+    /// optimizer step. `validate` evaluates with the averaged weights from then on.
+    pub fn enable_ema(&mut self, decay: Dtype) {
+        self.ema = Some(ExponentialMovingAverage::new(decay));
+        self.evaluate_with_ema = true;
+    }
+
+    /// Chooses whether `validate` uses the EMA weights (if enabled) or the training weights.
+    pub fn set_evaluate_with_ema(&mut self, enabled: bool) {
+        self.evaluate_with_ema = enabled;
+    }
+
+    /// Number of samples `validate` passes through the network at once, bounding the memory
+    /// needed for the activations of large validation sets.
+    pub fn set_validation_batch_size(&mut self, batch_size: usize) {
+        assert!(batch_size > 0, "validation batch size must be positive");
+        self.validation_batch_size = batch_size;
+    }
+
+    /// Runs `f` with the EMA weights swapped into the layers and restores the training
+    /// weights afterwards. Without EMA, `f` sees the training weights.
+    pub fn with_ema_weights<R>(&mut self, f: impl FnOnce(&mut Network) -> R) -> R {
+        if !self.ema.as_ref().is_some_and(|ema| ema.is_initialized()) {
+            return f(self);
+        }
+        let mut ema = self.ema.take().unwrap();
+
+        ema.swap(&mut self.layers);
+        let result = f(self);
+        ema.swap(&mut self.layers);
+
+        self.ema = Some(ema);
+        result
+    }
+
+    /// Runs `f` with the weights `validate` evaluates: the EMA weights if evaluation with EMA
+    /// is enabled, the training weights otherwise.
+    pub fn with_evaluation_weights<R>(&mut self, f: impl FnOnce(&mut Network) -> R) -> R {
+        if self.evaluate_with_ema {
+            self.with_ema_weights(f)
+        } else {
+            f(self)
+        }
+    }
+
+    /// Wraps the optimizer of every layer in a `Lookahead` with the given `k` and `alpha`.
+    /// Call it after all layers have been added.
+    pub fn use_lookahead(&mut self, k: usize, alpha: Dtype) {
+        let wrap = |inner: Box<dyn Optimizer>, input_size, output_size| -> Box<dyn Optimizer> {
+            Box::new(Lookahead::new(inner, k, alpha, input_size, output_size))
+        };
+        for layer in self.layers.iter_mut() {
+            layer.wrap_optimizer(&wrap);
+        }
+    }
+
+    /// Averages the weights at the end of every epoch from `start_epoch` (1-based) on, and
+    /// replaces the trained weights with that average when `train` finishes. The average wins
+    /// over weights restored by callbacks, e.g. `EarlyStopping::restore_best_weights`.
+    pub fn enable_swa(&mut self, start_epoch: usize) {
+        self.swa = Some(StochasticWeightAveraging::new(start_epoch));
+    }
+
+    /// Writes a full training checkpoint (see `save_checkpoint`) to `path` after every epoch.
+    pub fn set_checkpoint_path<P: Into<PathBuf>>(&mut self, path: P) {
+        self.checkpoint_path = Some(path.into());
+    }
+
+    /// Number of epochs trained so far, including epochs restored from a checkpoint.
+    pub fn completed_epochs(&self) -> usize {
+        self.completed_epochs
+    }
+
     /// Performs the forward pass through all layers.
     pub fn forward(&mut self, input: &Matrix) -> Matrix {
-        let mut output = input.clone();
+        forward_all(&mut self.layers, input)
+    }
+
+    /// Clips the gradients of all layers before every optimizer step.
+    pub fn set_gradient_clipping(&mut self, clipping: GradientClipping) {
+        self.gradient_clipping = clipping;
+    }
+
+    /// Global L2 norm of the gradients of the last backward pass, before clipping.
+    pub fn last_gradient_norm(&self) -> Option<Dtype> {
+        self.last_gradient_norm
+    }
+
+    /// Resets the accumulated gradients of all layers.
+    pub fn zero_grad(&mut self) {
         for layer in self.layers.iter_mut() {
-            output = layer.forward(&output);
+            layer.zero_grad();
         }
-        output
     }
 
-    /// Performs the backward pass (gradient descent).
+    /// Performs the backward pass, adding this batch's gradients to the accumulated ones.
+    /// No parameters are changed until `step` is called.
     pub fn backward(&mut self, y_true: &Matrix) {
+        self.backward_scaled(y_true, 1.0);
+    }
+
+    /// Backward pass with the loss gradient multiplied by `scale`, used to weight micro-batches
+    /// when accumulating gradients.
+    fn backward_scaled(&mut self, y_true: &Matrix, scale: Dtype) {
         let last_index = self.layers.len() - 1;
 
         let mut gradient = self.layers[last_index].backward(y_true);
+        if scale != 1.0 {
+            gradient = gradient * scale;
+        }
 
         for i in (0..last_index).rev() {
             gradient = self.layers[i].backward(&gradient);
         }
     }
 
-    /// Calculates the Categorical Cross-Entropy Loss.
+    /// Clips the accumulated gradients of all layers and lets every optimizer apply them.
+    pub fn step(&mut self) {
+        let mut gradients: Vec<&mut Matrix> = self
+            .layers
+            .iter_mut()
+            .flat_map(|layer| layer.parameters_mut())
+            .map(|parameter| parameter.gradient)
+            .collect();
+        self.last_gradient_norm = Some(self.gradient_clipping.apply(&mut gradients));
+
+        for layer in self.layers.iter_mut() {
+            layer.step();
+        }
+
+        if let Some(ema) = self.ema.as_mut() {
+            ema.update(&mut self.layers);
+        }
+    }
+
+    /// Whether `calculate_loss` adds the layers' regularization penalties to the loss.
+    pub fn set_include_regularization_in_loss(&mut self, enabled: bool) {
+        self.include_regularization_in_loss = enabled;
+    }
+
+    /// Sum of the regularization penalties of all layers.
+    pub fn regularization_loss(&self) -> Dtype {
+        self.layers
+            .iter()
+            .map(|layer| layer.regularization_loss())
+            .sum()
+    }
+
+    /// Calculates the Categorical Cross-Entropy Loss, plus the regularization penalty
+    /// if enabled with `set_include_regularization_in_loss`.
     pub fn calculate_loss(&self, y_pred: &Matrix, y_true: &Matrix) -> Dtype {
         let batch_size = y_pred.cols as Dtype;
         let num_classes = y_pred.rows;
@@ -71,7 +331,13 @@ impl Network {
         }
 
         // Return average negative log-likelihood
-        -loss / batch_size
+        let loss = -loss / batch_size;
+
+        if self.include_regularization_in_loss {
+            loss + self.regularization_loss()
+        } else {
+            loss
+        }
     }
 
     /// Calculates the classification accuracy.
@@ -108,65 +374,238 @@ impl Network {
         correct_predictions as Dtype / batch_size as Dtype
     }
 
-    /// Training loop executes all registered callbacks.
+    /// Trains for `epochs` epochs (fewer if a callback stops early) and returns the metrics of
+    /// every epoch run. With `validation` data, the validation loss and accuracy are computed at
+    /// the end of each epoch with `validate`. The metrics of each epoch are passed to all
+    /// callbacks as one shared `Logs` map, and the callbacks' epoch and batch hooks can skip work
+    /// or stop training.
+    ///
+    /// Every call starts a new run from epoch 1 with the current weights. Use `resume_training`
+    /// to continue a run restored with `load_checkpoint`.
     pub fn train(
         &mut self,
         input_x: &Matrix,
         y_true: &Matrix,
+        validation: Option<(&Matrix, &Matrix)>,
+        epochs: usize,
+        batch_size: usize,
+    ) -> anyhow::Result<History> {
+        self.completed_epochs = 0;
+        self.step_count = 0;
+        self.resume_training(input_x, y_true, validation, epochs, batch_size)
+    }
+
+    /// Like `train`, but continues after `completed_epochs` and stops once `epochs` epochs have
+    /// been trained in total, so a run restored with `load_checkpoint` picks up where it was
+    /// saved. On a network that has not been trained yet this is the same as `train`.
+    ///
+    /// Checkpoints do not include callback state: callbacks such as `EarlyStopping` or
+    /// `ReduceLrOnPlateau` start counting again from the resumed epoch.
+    pub fn resume_training(
+        &mut self,
+        input_x: &Matrix,
+        y_true: &Matrix,
+        validation: Option<(&Matrix, &Matrix)>,
         epochs: usize,
         batch_size: usize,
-    ) -> anyhow::Result<()> {
-        for callback in self.callbacks.iter_mut() {
-            callback.on_train_begin();
+    ) -> anyhow::Result<History> {
+        let mut callbacks_vec = std::mem::take(&mut self.callbacks);
+        for callback in callbacks_vec.iter_mut() {
+            callback.on_train_begin(self);
         }
+        self.callbacks = callbacks_vec;
 
         let progress = indicatif::MultiProgress::new();
 
-        let bar_batches = progress.add(ProgressBar::new(input_x.cols as u64 / batch_size as u64));
+        let bar_batches = progress.add(ProgressBar::new(input_x.cols.div_ceil(batch_size) as u64));
         bar_batches.set_style(self.bar_style.clone());
 
+        let start_epoch = self.completed_epochs;
+        if start_epoch > 0 {
+            log::info!("Resuming training after epoch {}.", start_epoch);
+        }
+
         let bar_epochs = progress.add(ProgressBar::new(epochs as u64));
         bar_epochs.set_style(self.bar_style.clone());
+        bar_epochs.set_position(start_epoch as u64);
+
+        if start_epoch == 0
+            && let Some(swa) = self.swa.as_mut()
+        {
+            swa.reset();
+        }
+        if start_epoch == 0 || self.base_learning_rates.is_none() {
+            self.base_learning_rates = Some(self.learning_rates());
+        }
+
+        let base_learning_rates = self.base_learning_rates.clone().unwrap_or_default();
+        let schedule_interval = self.lr_scheduler.as_ref().map(|(_, interval)| *interval);
 
-        let mut x_epoch = input_x.clone();
-        let mut y_epoch = y_true.clone();
-        let mut stop_training = false;
-        for _epoch in 1..=epochs {
+        // An empty validation set has no meaningful metrics.
+        let validation = validation.filter(|(x_valid, _)| x_valid.cols > 0);
+        let mut history = History::default();
+        for epoch in (start_epoch + 1)..=epochs {
             bar_epochs.inc(1);
-            if stop_training {
-                break;
+            if schedule_interval == Some(ScheduleInterval::Epoch) {
+                self.apply_lr_schedule(&base_learning_rates, epoch - 1);
             }
-            let indices = x_epoch.generate_shuffled_indices();
 
+            let context = self.training_context(epoch, 0, Logs::new());
+            match self.run_callbacks(|callback, net| callback.on_epoch_begin(net, &context)) {
+                CallbackAction::Stop => break,
+                CallbackAction::Skip => {
+                    self.complete_epoch(epoch)?;
+                    continue;
+                }
+                CallbackAction::Continue => {}
+            }
+
+            // A fresh permutation of the original data every epoch, drawn from the network's
+            // RNG so that a restored checkpoint continues with the same sequence.
+            let mut indices: Vec<usize> = (0..input_x.cols).collect();
+            indices.shuffle(&mut self.shuffle_rng);
+
+            let mut x_epoch = input_x.clone();
+            let mut y_epoch = y_true.clone();
             x_epoch.shuffle_columns(&indices);
             y_epoch.shuffle_columns(&indices);
 
             let x_batches = x_epoch.split_into_batches(batch_size);
             let y_batches = y_epoch.split_into_batches(batch_size);
 
-            for (i, (x_batch, y_batch)) in x_batches.iter().zip(y_batches.iter()).enumerate() {
-                let y_pred = self.forward(x_batch);
-                self.backward(y_batch);
-
-                if i % 100 == 0 {
-                    bar_batches.inc(100);
-
-                    let mut callbacks_vec = std::mem::take(&mut self.callbacks);
+            // Sample-weighted sums of the batch metrics, for the epoch means.
+            let (mut loss_sum, mut accuracy_sum, mut epoch_samples) = (0.0, 0.0, 0);
+            let mut batch_index = 0;
+            let mut end_epoch = false;
+            let mut stop_training = false;
+
+            // One optimizer step per window of `accumulation_steps` micro-batches.
+            for (x_window, y_window) in x_batches
+                .chunks(self.accumulation_steps)
+                .zip(y_batches.chunks(self.accumulation_steps))
+            {
+                if schedule_interval == Some(ScheduleInterval::Step) {
+                    self.apply_lr_schedule(&base_learning_rates, self.step_count);
+                }
 
-                    for callback in callbacks_vec.iter_mut() {
-                        stop_training =
-                            callback.on_epoch_end(self, &y_pred, y_batch) || stop_training;
+                // Weight each micro-batch by its share of the window, so the accumulated
+                // gradient is the mean over all samples of the window.
+                let window_size: usize = x_window.iter().map(|batch| batch.cols).sum();
+                let mut window_samples = 0;
+
+                self.zero_grad();
+                for (x_batch, y_batch) in x_window.iter().zip(y_window.iter()) {
+                    let context = self.training_context(epoch, batch_index, Logs::new());
+                    batch_index += 1;
+                    let action =
+                        self.run_callbacks(|callback, net| callback.on_batch_begin(net, &context));
+                    match action {
+                        CallbackAction::Stop => {
+                            (end_epoch, stop_training) = (true, true);
+                            break;
+                        }
+                        CallbackAction::Skip => {
+                            bar_batches.inc(1);
+                            continue;
+                        }
+                        CallbackAction::Continue => {}
                     }
 
-                    self.callbacks = callbacks_vec;
+                    let y_pred = self.forward(x_batch);
+                    self.backward_scaled(y_batch, x_batch.cols as Dtype / window_size as Dtype);
 
+                    let samples = x_batch.cols as Dtype;
                     let loss = self.calculate_loss(&y_pred, y_batch);
                     let accuracy = self.calculate_accuracy(&y_pred, y_batch);
-                    bar_epochs.set_message(format!("Loss: {:.6} | Acc: {:.4}", loss, accuracy));
+                    loss_sum += loss * samples;
+                    accuracy_sum += accuracy * samples;
+                    window_samples += x_batch.cols;
+                    bar_batches.inc(1);
+
+                    let logs =
+                        Logs::from([(LOSS.to_string(), loss), (ACCURACY.to_string(), accuracy)]);
+                    let context = TrainingContext { logs, ..context };
+                    match self.run_callbacks(|callback, net| callback.on_batch_end(net, &context)) {
+                        CallbackAction::Stop => (end_epoch, stop_training) = (true, true),
+                        CallbackAction::Skip => end_epoch = true,
+                        CallbackAction::Continue => {}
+                    }
+                    if end_epoch {
+                        break;
+                    }
+                }
+
+                // Skipped batches leave no gradient, so rescale to the mean over the samples used.
+                if window_samples > 0 {
+                    if window_samples < window_size {
+                        self.scale_gradients(window_size as Dtype / window_samples as Dtype);
+                    }
+                    self.step_count += 1;
+                    self.step();
+                }
+                epoch_samples += window_samples;
+                if end_epoch {
+                    break;
                 }
             }
 
             bar_batches.reset();
+
+            // An epoch whose batches were all skipped has no metrics to report.
+            if epoch_samples == 0 {
+                log::warn!("Epoch {} trained on no samples, not recording it.", epoch);
+                self.complete_epoch(epoch)?;
+                if stop_training {
+                    break;
+                }
+                continue;
+            }
+
+            if let Some(swa) = self.swa.as_mut()
+                && epoch >= swa.start_epoch
+            {
+                swa.update(&mut self.layers);
+            }
+
+            let (val_loss, val_accuracy) = match validation {
+                Some((x_valid, y_valid)) => {
+                    let (loss, accuracy) = self.validate(x_valid, y_valid);
+                    (Some(loss), Some(accuracy))
+                }
+                None => (None, None),
+            };
+            let metric = TrainingMetric {
+                epoch: epoch as u32,
+                loss: loss_sum / epoch_samples as Dtype,
+                accuracy: accuracy_sum / epoch_samples as Dtype,
+                val_loss,
+                val_accuracy,
+            };
+            history.epochs.push(metric);
+            bar_epochs.set_message(match (val_loss, val_accuracy) {
+                (Some(val_loss), Some(val_accuracy)) => format!(
+                    "Loss: {:.6} | Acc: {:.4} | Val loss: {:.6} | Val acc: {:.4}",
+                    metric.loss, metric.accuracy, val_loss, val_accuracy
+                ),
+                _ => format!("Loss: {:.6} | Acc: {:.4}", metric.loss, metric.accuracy),
+            });
+
+            let logs = metric.to_logs();
+            if validation.is_some() {
+                let context = self.training_context(epoch, batch_index, logs.clone());
+                let action =
+                    self.run_callbacks(|callback, net| callback.on_validation_end(net, &context));
+                stop_training = stop_training || action == CallbackAction::Stop;
+            }
+
+            let context = self.training_context(epoch, batch_index, logs);
+            let action = self.run_callbacks(|callback, net| callback.on_epoch_end(net, &context));
+            stop_training = stop_training || action == CallbackAction::Stop;
+
+            self.complete_epoch(epoch)?;
+            if stop_training {
+                break;
+            }
         }
 
         let mut callbacks_vec = std::mem::take(&mut self.callbacks);
@@ -177,16 +616,83 @@ impl Network {
 
         self.callbacks = callbacks_vec;
 
+        // After `on_train_end`, so the average also replaces weights restored by callbacks.
+        if let Some(swa) = self.swa.as_ref() {
+            log::info!("Loading SWA weights averaged over {} epochs.", swa.count());
+            swa.apply(&mut self.layers);
+        }
+
         bar_epochs.finish_with_message("Training Complete.");
         log::info!("Training finished successfully.");
 
+        Ok(history)
+    }
+
+    /// Records `epoch` as completed and writes the checkpoint, if one is configured.
+    fn complete_epoch(&mut self, epoch: usize) -> anyhow::Result<()> {
+        self.completed_epochs = epoch;
+        if let Some(path) = self.checkpoint_path.clone() {
+            self.save_checkpoint(path)?;
+        }
         Ok(())
     }
 
+    fn training_context(&mut self, epoch: usize, batch: usize, logs: Logs) -> TrainingContext {
+        TrainingContext {
+            epoch,
+            batch,
+            step: self.step_count,
+            learning_rates: self.learning_rates(),
+            logs,
+        }
+    }
+
+    /// Calls `hook` for every callback and combines the actions they request.
+    fn run_callbacks(
+        &mut self,
+        mut hook: impl FnMut(&mut dyn Callback, &mut Network) -> CallbackAction,
+    ) -> CallbackAction {
+        let mut callbacks_vec = std::mem::take(&mut self.callbacks);
+        let action = callbacks_vec
+            .iter_mut()
+            .map(|callback| hook(callback.as_mut(), self))
+            .max()
+            .unwrap_or_default();
+        self.callbacks = callbacks_vec;
+        action
+    }
+
+    /// Multiplies the accumulated gradients of all layers by `scale`.
+    fn scale_gradients(&mut self, scale: Dtype) {
+        for parameter in self
+            .layers
+            .iter_mut()
+            .flat_map(|layer| layer.parameters_mut())
+        {
+            parameter.gradient.data.iter_mut().for_each(|g| *g *= scale);
+        }
+    }
+
+    /// Loss and accuracy on the given data, using the EMA weights if configured. The data is
+    /// evaluated in batches (see `set_validation_batch_size`) and the metrics are averaged
+    /// over all samples.
     pub fn validate(&mut self, input_x: &Matrix, y_true: &Matrix) -> (Dtype, Dtype) {
-        let y_pred = self.forward(input_x);
-        let loss = self.calculate_loss(&y_pred, y_true);
-        let accuracy = self.calculate_accuracy(&y_pred, y_true);
-        (loss, accuracy)
+        self.with_evaluation_weights(|net| net.validate_batches(input_x, y_true))
+    }
+
+    fn validate_batches(&mut self, input_x: &Matrix, y_true: &Matrix) -> (Dtype, Dtype) {
+        let x_batches = input_x.split_into_batches(self.validation_batch_size);
+        let y_batches = y_true.split_into_batches(self.validation_batch_size);
+
+        let (mut loss_sum, mut accuracy_sum) = (0.0, 0.0);
+        for (x_batch, y_batch) in x_batches.iter().zip(y_batches.iter()) {
+            let y_pred = self.forward(x_batch);
+            let samples = x_batch.cols as Dtype;
+            loss_sum += self.calculate_loss(&y_pred, y_batch) * samples;
+            accuracy_sum += self.calculate_accuracy(&y_pred, y_batch) * samples;
+        }
+
+        let samples = input_x.cols as Dtype;
+        (loss_sum / samples, accuracy_sum / samples)
     }
 }
@@ -0,0 +1,979 @@
+#[cfg(test)]
+mod tests {
+    #[cfg(target_endian = "little")]
+    use crate::networks::mapped_model::MappedModel;
+    use crate::{
+        Dtype,
+        callbacks::{
+            Callback,
+            training_context::{CallbackAction, TrainingContext},
+        },
+        data_structures::matrix::Matrix,
+        layers::{
+            Layer,
+            dense::{ConfigDenseLayer, DenseLayer},
+            relu::ReLULayer,
+            softmax::Softmax,
+        },
+        networks::{
+            binary_format,
+            checkpoint::Checkpoint,
+            codegen::generate_rust,
+            gradient_clipping::GradientClipping,
+            history::{LOSS, Logs, VAL_LOSS},
+            network::Network,
+            serialization::LayerRecord,
+            spec::{LayerSpec, ModelSpec, OptimizerSpec},
+            swa::StochasticWeightAveraging,
+            test_fixtures::{batch, small_network},
+        },
+    };
+
+    fn weight_gradient(net: &mut Network) -> Matrix {
+        net.layers[0].parameters_mut()[0].gradient.clone()
+    }
+
+    fn gradients() -> (Matrix, Matrix) {
+        let mut a = Matrix::new(2, 1);
+        a.set(0, 0, 3.0);
+        a.set(1, 0, -4.0);
+        let mut b = Matrix::new(1, 1);
+        b.set(0, 0, 12.0);
+        (a, b)
+    }
+
+    #[test]
+    fn test_clip_by_global_norm() {
+        let (mut a, mut b) = gradients();
+        let norm = GradientClipping::by_global_norm(6.5).apply(&mut [&mut a, &mut b]);
+
+        // sqrt(9 + 16 + 144) = 13, scaled down by half.
+        assert!((norm - 13.0).abs() < 1e-5);
+        assert!((a.get(0, 0) - 1.5).abs() < 1e-5);
+        assert!((a.get(1, 0) + 2.0).abs() < 1e-5);
+        assert!((b.get(0, 0) - 6.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_clip_by_value() {
+        let (mut a, mut b) = gradients();
+        let norm = GradientClipping::by_value(3.5).apply(&mut [&mut a, &mut b]);
+
+        assert!((norm - 13.0).abs() < 1e-5);
+        assert_eq!(a.data, vec![3.0, -3.5]);
+        assert_eq!(b.data, vec![3.5]);
+    }
+
+    #[test]
+    fn test_no_clipping_below_max_norm() {
+        let (mut a, mut b) = gradients();
+        GradientClipping::by_global_norm(20.0).apply(&mut [&mut a, &mut b]);
+        assert_eq!(a.data, vec![3.0, -4.0]);
+        assert_eq!(b.data, vec![12.0]);
+    }
+
+    #[test]
+    fn test_backward_accumulates_without_updating() {
+        let mut net = small_network(0.01);
+        let (x, y) = batch();
+        let weights = net.layers[0].get_weights().unwrap().clone();
+
+        net.zero_grad();
+        net.forward(&x);
+        net.backward(&y);
+        let once = weight_gradient(&mut net);
+        net.forward(&x);
+        net.backward(&y);
+        let twice = weight_gradient(&mut net);
+
+        assert_eq!(net.layers[0].get_weights().unwrap().data, weights.data);
+        for (a, b) in once.data.iter().zip(twice.data.iter()) {
+            assert!((2.0 * a - b).abs() < 1e-6);
+        }
+
+        net.step();
+        assert_ne!(net.layers[0].get_weights().unwrap().data, weights.data);
+
+        net.zero_grad();
+        assert!(weight_gradient(&mut net).data.iter().all(|g| *g == 0.0));
+    }
+
+    #[test]
+    fn test_accumulation_matches_full_batch() {
+        let x = Matrix::new_seeded_random(3, 6, 11);
+        let mut y = Matrix::new(2, 6);
+        (0..6).for_each(|c| y.set(c % 2, c, 1.0));
+
+        let mut full = small_network(0.01);
+        full.train(&x, &y, None, 2, 6).unwrap();
+
+        // Micro-batches of 4 + 2 samples must give the same mean gradient as one batch of 6.
+        let mut accumulated = small_network(0.01);
+        accumulated.set_accumulation_steps(2);
+        accumulated.train(&x, &y, None, 2, 4).unwrap();
+
+        let a = full.layers[0].get_weights().unwrap();
+        let b = accumulated.layers[0].get_weights().unwrap();
+        for (a, b) in a.data.iter().zip(b.data.iter()) {
+            assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_ema_evaluation_keeps_training_weights() {
+        let (x, y) = batch();
+        let mut net = small_network(0.01);
+        net.enable_ema(0.5);
+        let initial = net.layers[0].get_weights().unwrap().clone();
+
+        for _ in 0..2 {
+            net.zero_grad();
+            net.forward(&x);
+            net.backward(&y);
+            net.step();
+        }
+        let trained = net.layers[0].get_weights().unwrap().clone();
+
+        // Shadow is initialized after the first step, so it lags behind the training weights.
+        let averaged = net.with_ema_weights(|net| net.layers[0].get_weights().unwrap().clone());
+        assert_ne!(averaged.data, trained.data);
+        assert_ne!(averaged.data, initial.data);
+
+        net.validate(&x, &y);
+        assert_eq!(net.layers[0].get_weights().unwrap().data, trained.data);
+    }
+
+    #[test]
+    fn test_swa_averages_snapshots() {
+        let mut net = small_network(0.01);
+        let mut swa = StochasticWeightAveraging::new(1);
+
+        for value in [1.0, 2.0, 6.0] {
+            for parameter in net.layers[0].parameters_mut() {
+                parameter.value.data.fill(value);
+            }
+            swa.update(&mut net.layers);
+        }
+        swa.apply(&mut net.layers);
+
+        assert_eq!(swa.count(), 3);
+        assert!(
+            net.layers[0]
+                .get_weights()
+                .unwrap()
+                .data
+                .iter()
+                .all(|w| (w - 3.0).abs() < 1e-6)
+        );
+        assert!(
+            net.layers[0]
+                .get_biases()
+                .unwrap()
+                .data
+                .iter()
+                .all(|b| (b - 3.0).abs() < 1e-6)
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let (x, _) = batch();
+        let mut net = small_network(0.01);
+        let path = std::env::temp_dir().join("neural_networks_round_trip.json");
+
+        net.save(&path).unwrap();
+        let mut loaded = Network::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.layers.len(), 2);
+        assert_eq!(net.forward(&x).data, loaded.forward(&x).data);
+    }
+
+    // A layer defined outside the crate, implementing only the required methods.
+    struct Identity;
+
+    impl Layer for Identity {
+        fn get_weights(&self) -> Option<&Matrix> {
+            None
+        }
+        fn get_biases(&self) -> Option<&Matrix> {
+            None
+        }
+        fn forward(&mut self, input: &Matrix) -> Matrix {
+            input.clone()
+        }
+        fn backward(&mut self, output_gradient: &Matrix) -> Matrix {
+            output_gradient.clone()
+        }
+    }
+
+    #[test]
+    fn test_custom_layers_train_but_cannot_be_saved() {
+        let (x, y) = batch();
+        let mut net = small_network(0.01);
+        net.add_layer(Identity);
+        net.train(&x, &y, None, 1, 4).unwrap();
+
+        let path = std::env::temp_dir().join("neural_networks_custom_layer.json");
+        let err = net.save(&path).err().unwrap();
+        assert!(format!("{:#}", err).contains("layer 2: unsupported layer type"));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_load_rejects_other_versions() {
+        let path = std::env::temp_dir().join("neural_networks_old_version.json");
+        std::fs::write(
+            &path,
+            r#"{"format": "neural-networks/model", "version": 0, "layers": []}"#,
+        )
+        .unwrap();
+
+        let err = Network::load(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(format!("{:#}", err).contains("unsupported model format version 0"));
+    }
+
+    #[test]
+    fn test_loaded_dense_layers_keep_the_configured_weight_decay() {
+        let net = small_network(0.01);
+        let config = ConfigDenseLayer {
+            weight_decay: 0.1,
+            ..ConfigDenseLayer::default()
+        };
+        let path = std::env::temp_dir().join("neural_networks_weight_decay.json");
+        net.save(&path).unwrap();
+        let from_json = Network::load_with_config(&path, &config);
+        std::fs::remove_file(&path).unwrap();
+        let bytes = binary_format::encode(&net).unwrap();
+        let from_binary = binary_format::decode(&bytes, &config);
+
+        let before = net.layers[0].get_weights().unwrap().clone();
+        for mut loaded in [from_json.unwrap(), from_binary.unwrap()] {
+            // Without a data gradient, only the L2 penalty moves the weights, towards zero.
+            loaded.zero_grad();
+            loaded.step();
+            let after = loaded.layers[0].get_weights().unwrap();
+            for (b, a) in before.data.iter().zip(after.data.iter()) {
+                assert!(a.abs() < b.abs(), "{} did not shrink to {}", b, a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let (x, _) = batch();
+        let mut net = small_network(0.01);
+        let path = std::env::temp_dir().join("neural_networks_round_trip.bin");
+        net.save_binary(&path).unwrap();
+
+        let mut loaded = Network::load_binary(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(net.forward(&x).data, loaded.forward(&x).data);
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_mapped_inference_matches_network() {
+        let (x, _) = batch();
+        let mut net = small_network(0.01);
+        let path = std::env::temp_dir().join("neural_networks_mapped.bin");
+        net.save_binary(&path).unwrap();
+
+        let mapped = MappedModel::open(&path).unwrap();
+        let expected = net.forward(&x);
+        for (a, b) in expected.data.iter().zip(mapped.forward(&x).data.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_binary_detects_truncation_and_corruption() {
+        let bytes = binary_format::encode(&small_network(0.01)).unwrap();
+        let config = ConfigDenseLayer::default();
+        assert!(binary_format::decode(&bytes, &config).is_ok());
+
+        let truncated = &bytes[..bytes.len() - 9];
+        assert!(binary_format::decode(truncated, &config).is_err());
+
+        let mut corrupted = bytes.clone();
+        corrupted[bytes.len() / 2] ^= 0xff;
+        let err = binary_format::decode(&corrupted, &config).err().unwrap();
+        assert!(err.to_string().contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn test_resumed_training_matches_uninterrupted() {
+        let x = Matrix::new_seeded_random(3, 8, 5);
+        let mut y = Matrix::new(2, 8);
+        (0..8).for_each(|c| y.set(c % 2, c, 1.0));
+        let path = std::env::temp_dir().join("neural_networks_resume.ckpt.json");
+
+        let mut uninterrupted = small_network(0.01);
+        uninterrupted.enable_ema(0.9);
+        uninterrupted.train(&x, &y, None, 4, 3).unwrap();
+
+        let mut interrupted = small_network(0.01);
+        interrupted.enable_ema(0.9);
+        interrupted.set_checkpoint_path(&path);
+        interrupted.train(&x, &y, None, 2, 3).unwrap();
+
+        let mut resumed = small_network(0.01);
+        resumed.enable_ema(0.9);
+        resumed.load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resumed.completed_epochs(), 2);
+        resumed.resume_training(&x, &y, None, 4, 3).unwrap();
+
+        assert_eq!(
+            uninterrupted.layers[0].get_weights().unwrap().data,
+            resumed.layers[0].get_weights().unwrap().data
+        );
+        assert_eq!(
+            uninterrupted.with_ema_weights(|net| net.forward(&x)).data,
+            resumed.with_ema_weights(|net| net.forward(&x)).data
+        );
+    }
+
+    #[test]
+    fn test_train_twice_runs_all_epochs_again() {
+        let (x, y) = batch();
+        let mut net = small_network(0.01);
+        assert_eq!(net.train(&x, &y, None, 3, 2).unwrap().epochs.len(), 3);
+
+        let history = net.train(&x, &y, None, 3, 2).unwrap();
+        assert_eq!(history.epochs.len(), 3);
+        assert_eq!(history.epochs[0].epoch, 1);
+        assert_eq!(net.completed_epochs(), 3);
+
+        // Resuming a finished run has nothing left to do.
+        assert!(net.resume_training(&x, &y, None, 3, 2).unwrap().epochs.is_empty());
+    }
+
+    #[test]
+    fn test_load_checkpoint_rejects_other_architecture() {
+        let path = std::env::temp_dir().join("neural_networks_mismatch.ckpt.json");
+        small_network(0.01).save_checkpoint(&path).unwrap();
+
+        let mut other = Network::new();
+        other.add_layer(DenseLayer::new(3, 4, &ConfigDenseLayer::default()));
+        other.add_layer(Softmax::new());
+        let err = other.load_checkpoint(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(format!("{:#}", err).contains("layer 0 has shape (4, 3)"));
+    }
+
+    #[test]
+    fn test_rejected_checkpoint_leaves_the_network_unchanged() {
+        let build = || {
+            let config = ConfigDenseLayer::default();
+            let mut net = Network::new();
+            net.add_layer(DenseLayer::new(3, 4, &config));
+            net.add_layer(ReLULayer::new());
+            net.add_layer(DenseLayer::new(4, 2, &config));
+            net.add_layer(Softmax::new());
+            net.use_lookahead(1, 0.5);
+            net.enable_ema(0.9);
+            net
+        };
+        let (x, y) = batch();
+        let mut trained = build();
+        trained.train(&x, &y, None, 2, 2).unwrap();
+        let json = serde_json::to_string(&Checkpoint::from_network(&trained).unwrap()).unwrap();
+
+        // Everything `restore` could overwrite.
+        let snapshot = |net: &mut Network| {
+            let parameters: Vec<Vec<f32>> = net
+                .layers
+                .iter_mut()
+                .flat_map(|layer| layer.parameters_mut())
+                .map(|parameter| parameter.value.data.clone())
+                .collect();
+            let optimizers: Vec<_> = net
+                .layers
+                .iter()
+                .map(|layer| serde_json::to_value(layer.optimizer().map(|o| o.state())).unwrap())
+                .collect();
+            let ema = net.ema.as_ref().unwrap().shadow().len();
+            (parameters, optimizers, ema, net.completed_epochs())
+        };
+
+        let tampers: [fn(&mut Checkpoint); 4] = [
+            |c| {
+                if let LayerRecord::Dense { weights, .. } = &mut c.layers[2] {
+                    weights.data.pop();
+                }
+            },
+            |c| c.ema_shadow.as_mut().unwrap()[1] = Matrix::new(1, 1),
+            |c| {
+                c.ema_shadow.as_mut().unwrap().pop();
+            },
+            // The first layer's optimizer state loads before the last one is rejected.
+            |c| {
+                let state = c.optimizers[2].as_mut().unwrap();
+                state
+                    .tensors
+                    .insert("slow_w".to_string(), Matrix::new(2, 3));
+            },
+        ];
+        for (i, tamper) in tampers.into_iter().enumerate() {
+            let mut checkpoint = Checkpoint::from_json(&json).unwrap();
+            tamper(&mut checkpoint);
+
+            let mut net = build();
+            net.train(&x, &y, None, 1, 4).unwrap();
+            let before = snapshot(&mut net);
+            assert!(checkpoint.restore(&mut net).is_err(), "tamper {}", i);
+            assert!(snapshot(&mut net) == before, "tamper {}", i);
+        }
+
+        let mut net = build();
+        Checkpoint::from_json(&json)
+            .unwrap()
+            .restore(&mut net)
+            .unwrap();
+        assert!(snapshot(&mut net) == snapshot(&mut trained));
+    }
+
+    // Output of `generate_rust(&codegen_network())`, compiled into the test binary.
+    mod generated {
+        include!("fixtures/generated_predict.rs");
+    }
+
+    const GENERATED_FIXTURE: &str = "src/networks/fixtures/generated_predict.rs";
+
+    fn codegen_network() -> Network {
+        // Fixed values instead of random ones, so the fixture does not depend on the RNG.
+        let matrix = |rows: usize, cols: usize, offset: usize| Matrix {
+            rows,
+            cols,
+            data: (0..rows * cols)
+                .map(|i| ((i * 7 + offset) % 13) as f32 / 8.0 - 0.75)
+                .collect(),
+        };
+        let config = ConfigDenseLayer::default();
+        let mut net = Network::new();
+        net.add_layer(DenseLayer::from_parameters(
+            matrix(5, 4, 0),
+            matrix(5, 1, 3),
+            config.build_optimizer(4, 5),
+        ));
+        net.add_layer(ReLULayer::new());
+        net.add_layer(DenseLayer::from_parameters(
+            matrix(3, 5, 1),
+            matrix(3, 1, 5),
+            config.build_optimizer(5, 3),
+        ));
+        net.add_layer(Softmax::new());
+        net
+    }
+
+    #[test]
+    fn test_generated_code_matches_fixture() {
+        let source = generate_rust(&codegen_network()).unwrap();
+        if std::env::var_os("UPDATE_FIXTURES").is_some() {
+            std::fs::write(GENERATED_FIXTURE, &source).unwrap();
+        }
+        let fixture = std::fs::read_to_string(GENERATED_FIXTURE).unwrap();
+        assert!(
+            source == fixture,
+            "generated code differs from {}, rerun with UPDATE_FIXTURES=1 to update it",
+            GENERATED_FIXTURE
+        );
+    }
+
+    #[test]
+    fn test_generated_predict_matches_forward() {
+        let mut net = codegen_network();
+        let x = Matrix::new_seeded_random(4, 6, 13);
+        let expected = net.forward(&x);
+
+        for c in 0..x.cols {
+            let input: [f32; generated::INPUT_SIZE] =
+                std::array::from_fn(|r| x.get(r, c));
+            let output = generated::predict(&input);
+            for (r, value) in output.iter().enumerate() {
+                assert_eq!(*value, expected.get(r, c));
+            }
+        }
+    }
+
+    #[test]
+    fn test_model_spec_builds_same_network_as_hand_built() {
+        let spec = ModelSpec::from_json(
+            r#"{
+                "input_size": 3,
+                "layers": [
+                    { "type": "dense", "size": 4, "activation": "relu" },
+                    { "type": "dense", "size": 2 },
+                    { "type": "softmax" }
+                ],
+                "optimizer": { "type": "adam", "learning_rate": 0.01 },
+                "training": { "epochs": 5, "batch_size": 4 }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(spec.output_size(), Some(2));
+        assert_eq!((spec.training.epochs, spec.training.batch_size), (5, 4));
+
+        let config = ConfigDenseLayer {
+            learning_rate: 0.01,
+            ..ConfigDenseLayer::default()
+        };
+        let mut expected = Network::new();
+        expected.add_layer(DenseLayer::new(3, 4, &config));
+        expected.add_layer(ReLULayer::new());
+        expected.add_layer(DenseLayer::new(4, 2, &config));
+        expected.add_layer(Softmax::new());
+
+        let mut net = spec.build().unwrap();
+        let (x, y) = batch();
+        assert_eq!(net.forward(&x).data, expected.forward(&x).data);
+
+        // Same optimizer and hyperparameters, so training stays identical too.
+        net.train(&x, &y, None, 3, 2).unwrap();
+        expected.train(&x, &y, None, 3, 2).unwrap();
+        assert_eq!(net.forward(&x).data, expected.forward(&x).data);
+    }
+
+    #[test]
+    fn test_model_spec_layer_overrides_and_depth() {
+        let mut spec = ModelSpec::mlp(3, &[5, 5, 5, 5], 2);
+        if let LayerSpec::Dense { optimizer, .. } = &mut spec.layers[0] {
+            *optimizer = Some(OptimizerSpec::Lars {
+                learning_rate: Some(0.1),
+                momentum: None,
+                weight_decay: None,
+                trust_coefficient: None,
+                epsilon: None,
+            });
+        }
+        let json = serde_json::to_string(&spec).unwrap();
+        assert_eq!(ModelSpec::from_json(&json).unwrap(), spec);
+
+        let mut net = spec.build().unwrap();
+        // 5 dense layers, each followed by its activation.
+        assert_eq!(net.layers.len(), 10);
+        assert_eq!(net.learning_rates()[0], Some(0.1));
+        assert_eq!(net.learning_rates()[2], Some(0.001));
+
+        let zeros = ModelSpec::from_json(
+            r#"{ "input_size": 3, "layers": [{ "type": "dense", "size": 2, "init": { "type": "zeros" } }] }"#,
+        )
+        .unwrap();
+        let (x, _) = batch();
+        assert!(zeros.build().unwrap().forward(&x).data.iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_model_spec_errors() {
+        let no_dense = r#"{ "input_size": 3, "layers": [{ "type": "relu" }] }"#;
+        assert!(ModelSpec::from_json(no_dense).unwrap().build().is_err());
+
+        let bad_lookahead = r#"{ "input_size": 3, "layers": [{ "type": "dense", "size": 2 }],
+            "training": { "lookahead": { "k": 5, "alpha": 2.0 } } }"#;
+        assert!(ModelSpec::from_json(bad_lookahead).unwrap().build().is_err());
+
+        // Invalid training settings are errors, not panics inside the network setters.
+        let dense = ModelSpec::from_json(
+            r#"{ "input_size": 3, "layers": [{ "type": "dense", "size": 2 }] }"#,
+        )
+        .unwrap();
+        for ema_decay in [1.0, 1.5, -0.1, Dtype::NAN] {
+            let mut spec = dense.clone();
+            spec.training.ema_decay = Some(ema_decay);
+            assert!(spec.build().is_err(), "ema_decay = {}", ema_decay);
+        }
+        for max_norm in [0.0, -1.0, Dtype::NAN] {
+            let mut spec = dense.clone();
+            spec.training.max_gradient_norm = Some(max_norm);
+            assert!(spec.build().is_err(), "max_gradient_norm = {}", max_norm);
+        }
+        let mut spec = dense.clone();
+        spec.training.batch_size = 0;
+        assert!(spec.build().is_err());
+
+        let unknown_layer = r#"{ "input_size": 3, "layers": [{ "type": "conv", "size": 2 }] }"#;
+        assert!(ModelSpec::from_json(unknown_layer).is_err());
+
+        // Misspelled fields are rejected at every level instead of silently ignored.
+        for misspelled in [
+            r#"{ "input_size": 3, "layers": [], "optimiser": { "type": "adam" } }"#,
+            r#"{ "input_size": 3, "layers": [{ "type": "dense", "size": 2, "activaton": "relu" }] }"#,
+            r#"{ "input_size": 3, "layers": [], "optimizer": { "type": "adam", "learing_rate": 0.1 } }"#,
+            r#"{ "input_size": 3, "layers": [], "training": { "epoch": 5 } }"#,
+        ] {
+            assert!(ModelSpec::from_json(misspelled).is_err(), "{}", misspelled);
+        }
+    }
+
+    #[test]
+    fn test_model_spec_from_toml() {
+        let toml = r#"
+            input_size = 3
+
+            [[layers]]
+            type = "dense"
+            size = 4
+            activation = "relu"
+
+            [[layers]]
+            type = "dense"
+            size = 2
+            init = { type = "zeros" }
+
+            [optimizer]
+            type = "adamw"
+            learning_rate = 0.01
+
+            [training]
+            epochs = 5
+            lookahead = { k = 5, alpha = 0.5 }
+        "#;
+        let json = r#"{
+            "input_size": 3,
+            "layers": [
+                { "type": "dense", "size": 4, "activation": "relu" },
+                { "type": "dense", "size": 2, "init": { "type": "zeros" } }
+            ],
+            "optimizer": { "type": "adamw", "learning_rate": 0.01 },
+            "training": { "epochs": 5, "lookahead": { "k": 5, "alpha": 0.5 } }
+        }"#;
+        assert_eq!(
+            ModelSpec::from_toml(toml).unwrap(),
+            ModelSpec::from_json(json).unwrap()
+        );
+
+        let path = std::env::temp_dir().join("neural_networks_spec.toml");
+        std::fs::write(&path, toml).unwrap();
+        let spec = ModelSpec::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(spec.unwrap().output_size(), Some(2));
+
+        assert!(ModelSpec::from_toml("input_size = 3\nlayers = []\nepochs = 5").is_err());
+    }
+
+    // Records the logs of every epoch and stops after `stop_after` epochs.
+    struct LogRecorder {
+        logs: std::sync::Arc<std::sync::Mutex<Vec<(usize, Logs)>>>,
+        stop_after: usize,
+    }
+
+    impl Callback for LogRecorder {
+        fn on_epoch_end(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            let mut recorded = self.logs.lock().unwrap();
+            recorded.push((ctx.epoch, ctx.logs.clone()));
+            if recorded.len() >= self.stop_after {
+                CallbackAction::Stop
+            } else {
+                CallbackAction::Continue
+            }
+        }
+
+        fn on_train_end(&mut self, _network: &mut Network) {}
+    }
+
+    #[test]
+    fn test_train_returns_epoch_history() {
+        let mut net = small_network(0.01);
+        let (x, y) = batch();
+
+        // With a single batch per epoch, the epoch loss is the loss before its step.
+        let initial_loss = {
+            let y_pred = net.forward(&x);
+            net.calculate_loss(&y_pred, &y)
+        };
+        let history = net.train(&x, &y, Some((&x, &y)), 3, 4).unwrap();
+
+        assert_eq!(history.epochs.len(), 3);
+        assert_eq!(
+            history.epochs.iter().map(|m| m.epoch).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!((history.epochs[0].loss - initial_loss).abs() < 1e-6);
+        assert_eq!(history.val_losses().len(), 3);
+
+        // Validation of the last epoch uses the final weights.
+        let (val_loss, val_accuracy) = net.validate(&x, &y);
+        assert_eq!(history.last().unwrap().val_loss, Some(val_loss));
+        assert_eq!(history.last().unwrap().val_accuracy, Some(val_accuracy));
+        assert!(net.train(&x, &y, None, 4, 4).unwrap().val_losses().is_empty());
+    }
+
+    #[test]
+    fn test_callbacks_run_once_per_epoch_and_can_stop() {
+        let mut net = small_network(0.01);
+        let (x, y) = batch();
+        let logs = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        net.add_callback(LogRecorder {
+            logs: logs.clone(),
+            stop_after: 2,
+        });
+
+        // Two batches per epoch, but a single call per epoch.
+        let history = net.train(&x, &y, None, 5, 2).unwrap();
+
+        let epochs: Vec<usize> = logs.lock().unwrap().iter().map(|(e, _)| *e).collect();
+        assert_eq!(epochs, vec![1, 2]);
+        assert_eq!(history.epochs.len(), 2);
+        assert_eq!(net.completed_epochs(), 2);
+    }
+
+    #[test]
+    fn test_callbacks_see_validation_metrics_in_logs() {
+        let mut net = small_network(0.01);
+        let (x, y) = batch();
+        let logs = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        net.add_callback(LogRecorder {
+            logs: logs.clone(),
+            stop_after: usize::MAX,
+        });
+
+        let history = net.train(&x, &y, Some((&x, &y)), 2, 4).unwrap();
+
+        let logs = logs.lock().unwrap();
+        assert_eq!(logs.len(), 2);
+        for ((_, epoch_logs), metric) in logs.iter().zip(&history.epochs) {
+            assert_eq!(*epoch_logs, metric.to_logs());
+            assert_eq!(epoch_logs.get(VAL_LOSS).copied(), metric.val_loss);
+        }
+    }
+
+    #[test]
+    fn test_validate_in_batches_matches_single_pass() {
+        let mut net = small_network(0.01);
+        let x = Matrix::new_seeded_random(3, 7, 11);
+        let mut y = Matrix::new(2, 7);
+        (0..7).for_each(|c| y.set(c % 2, c, 1.0));
+
+        let (loss, accuracy) = net.validate(&x, &y);
+        net.set_validation_batch_size(3);
+        let (batched_loss, batched_accuracy) = net.validate(&x, &y);
+
+        assert!((loss - batched_loss).abs() < 1e-6);
+        assert!((accuracy - batched_accuracy).abs() < 1e-6);
+    }
+
+    // Skips the first epoch and the first batch of every epoch, stops after a batch of the
+    // third epoch or after the first validation, and records every hook it sees.
+    struct Scripted {
+        events: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Scripted {
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    impl Callback for Scripted {
+        fn on_train_begin(&mut self, network: &mut Network) {
+            self.record(format!("train_begin {:?}", network.input_size()));
+        }
+
+        fn on_epoch_begin(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            match ctx.epoch {
+                1 => CallbackAction::Skip,
+                _ => CallbackAction::Continue,
+            }
+        }
+
+        fn on_batch_begin(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            self.record(format!("batch {}/{} step {}", ctx.epoch, ctx.batch, ctx.step));
+            match ctx.batch {
+                0 => CallbackAction::Skip,
+                _ => CallbackAction::Continue,
+            }
+        }
+
+        fn on_batch_end(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            assert!(ctx.logs.contains_key(LOSS));
+            assert_eq!(ctx.learning_rates, vec![Some(0.01), None]);
+            match ctx.epoch {
+                3 => CallbackAction::Stop,
+                _ => CallbackAction::Continue,
+            }
+        }
+
+        fn on_validation_end(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            let has_val_loss = ctx.logs.contains_key(VAL_LOSS);
+            self.record(format!("validation {} {}", ctx.epoch, has_val_loss));
+            CallbackAction::Stop
+        }
+
+        fn on_epoch_end(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            self.record(format!("epoch_end {}", ctx.epoch));
+            CallbackAction::Continue
+        }
+
+        fn on_train_end(&mut self, _network: &mut Network) {}
+    }
+
+    #[test]
+    fn test_callback_hooks_can_skip_and_stop() {
+        let mut net = small_network(0.01);
+        let (x, y) = batch();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        net.add_callback(Scripted {
+            events: events.clone(),
+        });
+
+        let history = net.train(&x, &y, None, 5, 2).unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "train_begin Some(3)",
+                "batch 2/0 step 0",
+                "batch 2/1 step 0",
+                "epoch_end 2",
+                "batch 3/0 step 1",
+                "batch 3/1 step 1",
+                "epoch_end 3",
+            ]
+        );
+        assert_eq!(
+            history.epochs.iter().map(|m| m.epoch).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(history.epochs.iter().all(|m| m.loss.is_finite()));
+        assert_eq!(net.completed_epochs(), 3);
+        assert_eq!(net.step_count, 2);
+    }
+
+    // Skips the first epoch and stops before the second.
+    struct SkipThenStop;
+
+    impl Callback for SkipThenStop {
+        fn on_epoch_begin(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            match ctx.epoch {
+                1 => CallbackAction::Skip,
+                2 => CallbackAction::Stop,
+                _ => CallbackAction::Continue,
+            }
+        }
+
+        fn on_epoch_end(
+            &mut self,
+            _network: &mut Network,
+            _ctx: &TrainingContext,
+        ) -> CallbackAction {
+            CallbackAction::Continue
+        }
+
+        fn on_train_end(&mut self, _network: &mut Network) {}
+    }
+
+    #[test]
+    fn test_skipped_epochs_are_checkpointed_and_stop_ends_before_the_epoch() {
+        let (x, y) = batch();
+        let path = std::env::temp_dir().join("neural_networks_skip_epoch.ckpt.json");
+        let mut net = small_network(0.01);
+        net.set_checkpoint_path(&path);
+        net.add_callback(SkipThenStop);
+
+        let history = net.train(&x, &y, None, 5, 2).unwrap();
+
+        // Epoch 1 is skipped but still checkpointed, epoch 2 never starts.
+        assert!(history.epochs.is_empty());
+        assert_eq!((net.completed_epochs(), net.step_count), (1, 0));
+        let mut restored = small_network(0.01);
+        restored.load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored.completed_epochs(), 1);
+    }
+
+    // Skips every batch of the first epoch.
+    struct SkipFirstEpochBatches;
+
+    impl Callback for SkipFirstEpochBatches {
+        fn on_batch_begin(
+            &mut self,
+            _network: &mut Network,
+            ctx: &TrainingContext,
+        ) -> CallbackAction {
+            match ctx.epoch {
+                1 => CallbackAction::Skip,
+                _ => CallbackAction::Continue,
+            }
+        }
+
+        fn on_epoch_end(
+            &mut self,
+            _network: &mut Network,
+            _ctx: &TrainingContext,
+        ) -> CallbackAction {
+            CallbackAction::Continue
+        }
+
+        fn on_train_end(&mut self, _network: &mut Network) {}
+    }
+
+    #[test]
+    fn test_epochs_without_samples_are_not_recorded() {
+        let mut net = small_network(0.01);
+        let (x, y) = batch();
+        let logs = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        net.add_callback(SkipFirstEpochBatches);
+        net.add_callback(LogRecorder {
+            logs: logs.clone(),
+            stop_after: usize::MAX,
+        });
+
+        let history = net.train(&x, &y, Some((&x, &y)), 2, 2).unwrap();
+
+        let epochs: Vec<usize> = logs.lock().unwrap().iter().map(|(e, _)| *e).collect();
+        assert_eq!(epochs, vec![2]);
+        assert_eq!(history.epochs.len(), 1);
+        assert!(history.epochs[0].loss.is_finite());
+        assert_eq!(history.best_epoch().unwrap().epoch, 2);
+        assert_eq!(net.completed_epochs(), 2);
+    }
+
+    #[test]
+    fn test_validation_end_sees_validation_metrics() {
+        let mut net = small_network(0.01);
+        let (x, y) = batch();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        net.add_callback(Scripted {
+            events: events.clone(),
+        });
+
+        let history = net.train(&x, &y, Some((&x, &y)), 5, 2).unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[events.len() - 2..], ["validation 2 true", "epoch_end 2"]);
+        assert_eq!(history.epochs.len(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{any::Any, path::Path};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data_structures::matrix::Matrix,
+    layers::{
+        Layer,
+        dense::{ConfigDenseLayer, DenseLayer},
+        relu::ReLULayer,
+        softmax::Softmax,
+    },
+    networks::network::Network,
+};
+
+/// Identifies files written by `Network::save`.
+pub const MODEL_FORMAT: &str = "neural-networks/model";
+/// Bumped whenever the layout of `ModelFile` changes incompatibly.
+pub const MODEL_FORMAT_VERSION: u32 = 1;
+
+/// Architecture and parameters of a single layer.
+/// Matrices are stored as-is, i.e. column-major `data` with explicit `rows` and `cols`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LayerRecord {
+    Dense {
+        input_size: usize,
+        output_size: usize,
+        weights: Matrix,
+        biases: Matrix,
+    },
+    ReLU,
+    Softmax,
+}
+
+impl LayerRecord {
+    /// Architecture and parameters of `layer`, as written by `Network::save`. Only the layer
+    /// types of this crate can be recorded.
+    pub fn from_layer(layer: &dyn Layer) -> anyhow::Result<LayerRecord> {
+        let layer_any: &dyn Any = layer;
+        if layer_any.is::<DenseLayer>()
+            && let (Some(weights), Some(biases)) = (layer.get_weights(), layer.get_biases())
+        {
+            Ok(LayerRecord::Dense {
+                input_size: weights.cols,
+                output_size: weights.rows,
+                weights: weights.clone(),
+                biases: biases.clone(),
+            })
+        } else if layer_any.is::<ReLULayer>() {
+            Ok(LayerRecord::ReLU)
+        } else if layer_any.is::<Softmax>() {
+            Ok(LayerRecord::Softmax)
+        } else {
+            Err(anyhow!("unsupported layer type, only Dense, ReLU and Softmax can be saved"))
+        }
+    }
+
+    /// Builds the layer, checking that the stored matrices match the declared sizes.
+    /// Dense layers get a fresh optimizer and the L2 penalty from `config`.
+    pub fn into_layer(self, config: &ConfigDenseLayer) -> anyhow::Result<Box<dyn Layer>> {
+        match self {
+            LayerRecord::Dense {
+                input_size,
+                output_size,
+                weights,
+                biases,
+            } => {
+                check_shape("weights", &weights, output_size, input_size)?;
+                check_shape("biases", &biases, output_size, 1)?;
+                Ok(Box::new(config.build_layer(weights, biases)))
+            }
+            LayerRecord::ReLU => Ok(Box::new(ReLULayer::new())),
+            LayerRecord::Softmax => Ok(Box::new(Softmax::new())),
+        }
+    }
+}
+
+pub(crate) fn check_shape(
+    name: &str,
+    matrix: &Matrix,
+    rows: usize,
+    cols: usize,
+) -> anyhow::Result<()> {
+    if matrix.rows != rows || matrix.cols != cols || matrix.data.len() != rows * cols {
+        return Err(anyhow!(
+            "{} have shape ({}, {}) with {} values, expected ({}, {})",
+            name,
+            matrix.rows,
+            matrix.cols,
+            matrix.data.len(),
+            rows,
+            cols
+        ));
+    }
+    Ok(())
+}
+
+/// On-disk JSON representation of a `Network`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelFile {
+    pub format: String,
+    pub version: u32,
+    pub layers: Vec<LayerRecord>,
+}
+
+impl ModelFile {
+    pub fn from_network(network: &Network) -> anyhow::Result<ModelFile> {
+        Ok(ModelFile {
+            format: MODEL_FORMAT.to_string(),
+            version: MODEL_FORMAT_VERSION,
+            layers: network.layer_records()?,
+        })
+    }
+
+    /// Parses a model file, rejecting unknown formats and versions before reading the layers.
+    pub fn from_json(json: &str) -> anyhow::Result<ModelFile> {
+        #[derive(Deserialize)]
+        struct Header {
+            format: Option<String>,
+            version: Option<u32>,
+        }
+
+        let header: Header = serde_json::from_str(json).context("model file is not valid JSON")?;
+        match header.format.as_deref() {
+            Some(MODEL_FORMAT) => {}
+            Some(other) => return Err(anyhow!("unknown model format '{}'", other)),
+            None => return Err(anyhow!("missing model format header")),
+        }
+        match header.version {
+            Some(MODEL_FORMAT_VERSION) => {}
+            Some(other) => {
+                return Err(anyhow!(
+                    "unsupported model format version {} (this build reads version {})",
+                    other,
+                    MODEL_FORMAT_VERSION
+                ));
+            }
+            None => return Err(anyhow!("missing model format version")),
+        }
+
+        serde_json::from_str(json).context("malformed model file")
+    }
+
+    pub fn into_network(self, config: &ConfigDenseLayer) -> anyhow::Result<Network> {
+        let mut network = Network::new();
+        for (i, record) in self.layers.into_iter().enumerate() {
+            let layer = record
+                .into_layer(config)
+                .with_context(|| format!("invalid layer {}", i))?;
+            network.layers.push(layer);
+        }
+        Ok(network)
+    }
+}
+
+impl Network {
+    /// Records of all layers, see `LayerRecord::from_layer`.
+    pub fn layer_records(&self) -> anyhow::Result<Vec<LayerRecord>> {
+        self.layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                LayerRecord::from_layer(layer.as_ref()).with_context(|| format!("layer {}", i))
+            })
+            .collect()
+    }
+
+    /// Writes the architecture and all weights and biases as JSON.
+    /// Optimizer state, callbacks and training settings are not saved.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string(&ModelFile::from_network(self)?)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("failed to write model to {}", path.display()))?;
+        log::info!("Saved model to {}", path.display());
+        Ok(())
+    }
+
+    /// Reads a network written by `save`. Dense layers get a default `Adam` optimizer,
+    /// use `load_with_config` to continue training with other hyperparameters.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Network> {
+        Network::load_with_config(path, &ConfigDenseLayer::default())
+    }
+
+    pub fn load_with_config<P: AsRef<Path>>(
+        path: P,
+        config: &ConfigDenseLayer,
+    ) -> anyhow::Result<Network> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read model from {}", path.display()))?;
+        ModelFile::from_json(&json)
+            .and_then(|model| model.into_network(config))
+            .with_context(|| format!("failed to load model from {}", path.display()))
+    }
+}
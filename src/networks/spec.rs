@@ -0,0 +1,470 @@
+//! Declarative description of a network: its layers, their optimizers and the training
+//! hyperparameters. A `ModelSpec` is read from JSON or TOML and turned into a `Network` by
+//! `build`. Unknown fields are rejected, so a misspelled setting is an error.
+//!
+//! ```json
+//! {
+//!   "input_size": 784,
+//!   "layers": [
+//!     { "type": "dense", "size": 64, "activation": "relu" },
+//!     { "type": "dense", "size": 10, "activation": "softmax", "init": { "type": "zeros" } }
+//!   ],
+//!   "optimizer": { "type": "adam", "learning_rate": 0.001, "weight_decay": 0.00001 },
+//!   "training": { "epochs": 30, "batch_size": 128, "ema_decay": 0.999 }
+//! }
+//! ```
+//!
+//! The same spec in TOML:
+//!
+//! ```toml
+//! input_size = 784
+//!
+//! [[layers]]
+//! type = "dense"
+//! size = 64
+//! activation = "relu"
+//!
+//! [[layers]]
+//! type = "dense"
+//! size = 10
+//! activation = "softmax"
+//! init = { type = "zeros" }
+//!
+//! [optimizer]
+//! type = "adam"
+//! learning_rate = 0.001
+//! weight_decay = 0.00001
+//!
+//! [training]
+//! epochs = 30
+//! batch_size = 128
+//! ema_decay = 0.999
+//! ```
+
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Dtype, SEED,
+    data_structures::matrix::Matrix,
+    layers::{
+        dense::{ConfigDenseLayer, DenseLayer},
+        optimizers::{
+            Optimizer, adagrad::AdaGrad, adam::Adam, adamw::AdamW, lamb::Lamb, lars::Lars,
+        },
+        regularization::Regularizer,
+        relu::ReLULayer,
+        softmax::Softmax,
+    },
+    networks::{gradient_clipping::GradientClipping, network::Network},
+};
+
+/// Activation appended after a dense layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    Relu,
+    Softmax,
+}
+
+/// How the weights and biases of a dense layer are initialized.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum InitSpec {
+    /// Seeded normal distribution, as in `DenseLayer::new`.
+    Random {
+        #[serde(default = "default_seed")]
+        seed: u64,
+    },
+    Zeros,
+}
+
+fn default_seed() -> u64 {
+    SEED
+}
+
+impl Default for InitSpec {
+    fn default() -> Self {
+        InitSpec::Random { seed: SEED }
+    }
+}
+
+/// Optimizer of a dense layer. Hyperparameters that are left out get the usual defaults.
+/// The `weight_decay` of `adam` and `adagrad` is coupled L2 decay, applied by the layer's
+/// `Regularizer` rather than by the optimizer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum OptimizerSpec {
+    Adam {
+        learning_rate: Option<Dtype>,
+        beta1: Option<Dtype>,
+        beta2: Option<Dtype>,
+        epsilon: Option<Dtype>,
+        weight_decay: Option<Dtype>,
+    },
+    #[serde(rename = "adamw")]
+    AdamW {
+        learning_rate: Option<Dtype>,
+        beta1: Option<Dtype>,
+        beta2: Option<Dtype>,
+        epsilon: Option<Dtype>,
+        weight_decay: Option<Dtype>,
+    },
+    #[serde(rename = "adagrad")]
+    AdaGrad {
+        learning_rate: Option<Dtype>,
+        epsilon: Option<Dtype>,
+        momentum: Option<Dtype>,
+        weight_decay: Option<Dtype>,
+    },
+    Lamb {
+        learning_rate: Option<Dtype>,
+        beta1: Option<Dtype>,
+        beta2: Option<Dtype>,
+        epsilon: Option<Dtype>,
+        weight_decay: Option<Dtype>,
+    },
+    Lars {
+        learning_rate: Option<Dtype>,
+        momentum: Option<Dtype>,
+        weight_decay: Option<Dtype>,
+        trust_coefficient: Option<Dtype>,
+        epsilon: Option<Dtype>,
+    },
+}
+
+impl Default for OptimizerSpec {
+    /// `Adam` with the hyperparameters of `ConfigDenseLayer::default`.
+    fn default() -> Self {
+        OptimizerSpec::adam(ConfigDenseLayer::default().learning_rate, 0.0)
+    }
+}
+
+impl OptimizerSpec {
+    pub fn adam(learning_rate: Dtype, weight_decay: Dtype) -> Self {
+        OptimizerSpec::Adam {
+            learning_rate: Some(learning_rate),
+            beta1: None,
+            beta2: None,
+            epsilon: None,
+            weight_decay: Some(weight_decay),
+        }
+    }
+
+    /// The layer's L2 penalty: the `weight_decay` of `adam` and `adagrad`, zero otherwise.
+    pub fn l2_penalty(&self) -> Dtype {
+        match *self {
+            OptimizerSpec::Adam { weight_decay, .. }
+            | OptimizerSpec::AdaGrad { weight_decay, .. } => weight_decay.unwrap_or(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Creates the optimizer for a layer with `(output_size, input_size)` weights.
+    pub fn build(&self, input_size: usize, output_size: usize) -> Box<dyn Optimizer> {
+        let lr = |v: Option<Dtype>| v.unwrap_or(ConfigDenseLayer::default().learning_rate);
+        let or = |v: Option<Dtype>, default: Dtype| v.unwrap_or(default);
+        match *self {
+            OptimizerSpec::Adam {
+                learning_rate,
+                beta1,
+                beta2,
+                epsilon,
+                weight_decay: _,
+            } => Box::new(Adam::new(
+                lr(learning_rate),
+                or(beta1, 0.9),
+                or(beta2, 0.999),
+                or(epsilon, 1e-8),
+                input_size,
+                output_size,
+            )),
+            OptimizerSpec::AdamW {
+                learning_rate,
+                beta1,
+                beta2,
+                epsilon,
+                weight_decay,
+            } => Box::new(AdamW::new(
+                lr(learning_rate),
+                or(beta1, 0.9),
+                or(beta2, 0.999),
+                or(epsilon, 1e-8),
+                or(weight_decay, 0.01),
+                input_size,
+                output_size,
+            )),
+            OptimizerSpec::AdaGrad {
+                learning_rate,
+                epsilon,
+                momentum,
+                weight_decay: _,
+            } => Box::new(AdaGrad::new(
+                lr(learning_rate),
+                or(epsilon, 1e-8),
+                or(momentum, 0.0),
+                input_size,
+                output_size,
+            )),
+            OptimizerSpec::Lamb {
+                learning_rate,
+                beta1,
+                beta2,
+                epsilon,
+                weight_decay,
+            } => Box::new(Lamb::new(
+                lr(learning_rate),
+                or(beta1, 0.9),
+                or(beta2, 0.999),
+                or(epsilon, 1e-6),
+                or(weight_decay, 0.0),
+                input_size,
+                output_size,
+            )),
+            OptimizerSpec::Lars {
+                learning_rate,
+                momentum,
+                weight_decay,
+                trust_coefficient,
+                epsilon,
+            } => Box::new(Lars::new(
+                lr(learning_rate),
+                or(momentum, 0.9),
+                or(weight_decay, 0.0),
+                or(trust_coefficient, 0.001),
+                or(epsilon, 1e-8),
+                input_size,
+                output_size,
+            )),
+        }
+    }
+}
+
+/// A layer of the network. `size` of a dense layer is its number of outputs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LayerSpec {
+    Dense {
+        size: usize,
+        #[serde(default)]
+        activation: Option<Activation>,
+        #[serde(default)]
+        init: InitSpec,
+        /// Overrides the optimizer of the model for this layer.
+        #[serde(default)]
+        optimizer: Option<OptimizerSpec>,
+    },
+    Relu,
+    Softmax,
+}
+
+/// Wraps every optimizer in a `Lookahead`, see `Network::use_lookahead`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LookaheadSpec {
+    pub k: usize,
+    pub alpha: Dtype,
+}
+
+/// Training hyperparameters. Only the network-level settings are applied by `build`,
+/// `epochs`, `batch_size` and `validation_split` are for the code that runs the training.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrainingSpec {
+    pub epochs: usize,
+    pub batch_size: usize,
+    pub validation_split: f32,
+    pub accumulation_steps: usize,
+    pub max_gradient_norm: Option<Dtype>,
+    pub ema_decay: Option<Dtype>,
+    pub lookahead: Option<LookaheadSpec>,
+}
+
+impl Default for TrainingSpec {
+    fn default() -> Self {
+        TrainingSpec {
+            epochs: 30,
+            batch_size: 128,
+            validation_split: 0.2,
+            accumulation_steps: 1,
+            max_gradient_norm: None,
+            ema_decay: None,
+            lookahead: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelSpec {
+    pub input_size: usize,
+    pub layers: Vec<LayerSpec>,
+    /// Optimizer of every dense layer without its own, `Adam` if not given.
+    #[serde(default)]
+    pub optimizer: OptimizerSpec,
+    #[serde(default)]
+    pub training: TrainingSpec,
+}
+
+impl ModelSpec {
+    /// A multi-layer perceptron: dense layers with ReLU of the given hidden sizes, followed by
+    /// a dense layer with softmax.
+    pub fn mlp(input_size: usize, hidden_sizes: &[usize], output_size: usize) -> ModelSpec {
+        let dense = |size, activation| LayerSpec::Dense {
+            size,
+            activation: Some(activation),
+            init: InitSpec::default(),
+            optimizer: None,
+        };
+        let mut layers: Vec<LayerSpec> = hidden_sizes
+            .iter()
+            .map(|&size| dense(size, Activation::Relu))
+            .collect();
+        layers.push(dense(output_size, Activation::Softmax));
+
+        ModelSpec {
+            input_size,
+            layers,
+            optimizer: OptimizerSpec::default(),
+            training: TrainingSpec::default(),
+        }
+    }
+
+    pub fn with_optimizer(mut self, optimizer: OptimizerSpec) -> ModelSpec {
+        self.optimizer = optimizer;
+        self
+    }
+
+    pub fn with_training(mut self, training: TrainingSpec) -> ModelSpec {
+        self.training = training;
+        self
+    }
+
+    pub fn from_json(json: &str) -> anyhow::Result<ModelSpec> {
+        serde_json::from_str(json).context("invalid model spec")
+    }
+
+    pub fn from_toml(toml: &str) -> anyhow::Result<ModelSpec> {
+        toml::from_str(toml).context("invalid model spec")
+    }
+
+    /// Reads a spec from a `.toml` file, or from JSON for any other extension.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<ModelSpec> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read model spec from {}", path.display()))?;
+        let spec = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => ModelSpec::from_toml(&text),
+            _ => ModelSpec::from_json(&text),
+        };
+        spec.with_context(|| format!("failed to load {}", path.display()))
+    }
+
+    /// Number of outputs of the last dense layer.
+    pub fn output_size(&self) -> Option<usize> {
+        self.layers.iter().rev().find_map(|layer| match layer {
+            LayerSpec::Dense { size, .. } => Some(*size),
+            _ => None,
+        })
+    }
+
+    /// Constructs the network with freshly initialized layers and applies the network-level
+    /// training settings (gradient clipping, accumulation, EMA, lookahead).
+    pub fn build(&self) -> anyhow::Result<Network> {
+        if self.input_size == 0 {
+            return Err(anyhow!("input_size must be positive"));
+        }
+        if self.output_size().is_none() {
+            return Err(anyhow!("the model needs at least one dense layer"));
+        }
+        let training = &self.training;
+        if training.accumulation_steps == 0 {
+            return Err(anyhow!("accumulation_steps must be positive"));
+        }
+        if training.batch_size == 0 {
+            return Err(anyhow!("batch_size must be positive"));
+        }
+        if let Some(max_norm) = training.max_gradient_norm
+            && (max_norm.is_nan() || max_norm <= 0.0)
+        {
+            return Err(anyhow!(
+                "max_gradient_norm must be positive, got {}",
+                max_norm
+            ));
+        }
+        if let Some(decay) = training.ema_decay
+            && !(0.0..1.0).contains(&decay)
+        {
+            return Err(anyhow!("ema_decay must be in [0, 1), got {}", decay));
+        }
+        if let Some(LookaheadSpec { k, alpha }) = training.lookahead
+            && (k == 0 || !(0.0..=1.0).contains(&alpha))
+        {
+            return Err(anyhow!(
+                "lookahead needs k > 0 and alpha in [0, 1], got k = {} and alpha = {}",
+                k,
+                alpha
+            ));
+        }
+
+        let mut net = Network::new();
+        let mut input_size = self.input_size;
+        for (i, layer) in self.layers.iter().enumerate() {
+            match layer {
+                LayerSpec::Dense {
+                    size,
+                    activation,
+                    init,
+                    optimizer,
+                } => {
+                    if *size == 0 {
+                        return Err(anyhow!("dense layer {} has size 0", i));
+                    }
+                    let optimizer = optimizer.as_ref().unwrap_or(&self.optimizer);
+                    let regularizer = Regularizer::l2(optimizer.l2_penalty());
+                    let optimizer = optimizer.build(input_size, *size);
+                    let (weights, biases) = match *init {
+                        InitSpec::Random { seed } => (
+                            Matrix::new_seeded_random(*size, input_size, seed),
+                            Matrix::new_seeded_random(*size, 1, seed),
+                        ),
+                        InitSpec::Zeros => (Matrix::new(*size, input_size), Matrix::new(*size, 1)),
+                    };
+                    net.add_layer(
+                        DenseLayer::from_parameters(weights, biases, optimizer)
+                            .with_regularizer(regularizer),
+                    );
+                    match activation {
+                        Some(Activation::Relu) => net.add_layer(ReLULayer::new()),
+                        Some(Activation::Softmax) => net.add_layer(Softmax::new()),
+                        None => {}
+                    }
+                    input_size = *size;
+                }
+                LayerSpec::Relu => net.add_layer(ReLULayer::new()),
+                LayerSpec::Softmax => net.add_layer(Softmax::new()),
+            }
+        }
+
+        net.set_accumulation_steps(training.accumulation_steps);
+        if let Some(max_norm) = training.max_gradient_norm {
+            net.set_gradient_clipping(GradientClipping::by_global_norm(max_norm));
+        }
+        if let Some(decay) = training.ema_decay {
+            net.enable_ema(decay);
+        }
+        if let Some(LookaheadSpec { k, alpha }) = training.lookahead {
+            net.use_lookahead(k, alpha);
+        }
+        Ok(net)
+    }
+}
+
+impl Network {
+    /// Builds a network from a `ModelSpec`, see `ModelSpec::build`.
+    pub fn from_spec(spec: &ModelSpec) -> anyhow::Result<Network> {
+        spec.build()
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{Dtype, data_structures::matrix::Matrix, layers::Layer};
+
+/// Stochastic weight averaging (Izmailov et al., "Averaging Weights Leads to Wider Optima").
+///
+/// Keeps an equal-weight running average of all parameters, sampled at the end of every
+/// epoch from `start_epoch` on. `Network::train` loads the average into the layers at the end.
+pub struct StochasticWeightAveraging {
+    pub start_epoch: usize,
+    // One matrix per parameter, in the order of `Layer::parameters_mut` over all layers.
+    average: Vec<Matrix>,
+    count: usize,
+}
+
+impl StochasticWeightAveraging {
+    pub fn new(start_epoch: usize) -> Self {
+        StochasticWeightAveraging {
+            start_epoch,
+            average: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Number of weight snapshots averaged so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The running average, one matrix per parameter (empty before the first snapshot).
+    pub fn average(&self) -> &[Matrix] {
+        &self.average
+    }
+
+    /// Replaces the running average of `count` snapshots, e.g. when restoring a checkpoint.
+    pub fn restore(&mut self, average: Vec<Matrix>, count: usize) {
+        self.average = average;
+        self.count = count;
+    }
+
+    /// Clears the running average, e.g. before a new training run.
+    pub fn reset(&mut self) {
+        self.average.clear();
+        self.count = 0;
+    }
+
+    /// Adds the current parameters to the running average.
+    pub fn update(&mut self, layers: &mut [Box<dyn Layer>]) {
+        let parameters = layers.iter_mut().flat_map(|layer| layer.parameters_mut());
+
+        if self.count == 0 {
+            self.average = parameters
+                .map(|parameter| parameter.value.clone())
+                .collect();
+        } else {
+            // avg <- avg + (value - avg) / (n + 1)
+            let weight = 1.0 / (self.count + 1) as Dtype;
+            for (average, parameter) in self.average.iter_mut().zip(parameters) {
+                for (a, v) in average.data.iter_mut().zip(parameter.value.data.iter()) {
+                    *a += (v - *a) * weight;
+                }
+            }
+        }
+
+        self.count += 1;
+    }
+
+    /// Overwrites the layer parameters with the average, if any snapshot was taken.
+    pub fn apply(&self, layers: &mut [Box<dyn Layer>]) {
+        if self.count == 0 {
+            return;
+        }
+
+        let parameters = layers.iter_mut().flat_map(|layer| layer.parameters_mut());
+        for (average, parameter) in self.average.iter().zip(parameters) {
+            parameter.value.data.copy_from_slice(&average.data);
+        }
+    }
+}
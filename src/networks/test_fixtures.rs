@@ -0,0 +1,32 @@
+//! Small networks and data shared by the tests of the training code.
+
+use crate::{
+    Dtype,
+    data_structures::matrix::Matrix,
+    layers::{
+        dense::{ConfigDenseLayer, DenseLayer},
+        softmax::Softmax,
+    },
+    networks::network::Network,
+};
+
+/// A dense layer from 3 inputs to 2 classes, followed by softmax.
+pub fn small_network(learning_rate: Dtype) -> Network {
+    let config = ConfigDenseLayer {
+        learning_rate,
+        momentum_factor: 0.0,
+        weight_decay: 0.0,
+    };
+    let mut net = Network::new();
+    net.add_layer(DenseLayer::new(3, 2, &config));
+    net.add_layer(Softmax::new());
+    net
+}
+
+/// Four samples for `small_network`, alternating between the two classes.
+pub fn batch() -> (Matrix, Matrix) {
+    let x = Matrix::new_seeded_random(3, 4, 7);
+    let mut y = Matrix::new(2, 4);
+    (0..4).for_each(|c| y.set(c % 2, c, 1.0));
+    (x, y)
+}
@@ -0,0 +1,242 @@
+//! Export of a `Network` as an ONNX model (opset 13).
+//!
+//! ONNX tensors are row-major and samples are rows, so the graph takes an input of shape
+//! `[batch, input_size]`. A dense layer becomes `Gemm(X, W, b, transB = 1)` with `W` stored as
+//! `[output_size, input_size]`, i.e. the transpose of the column-major `Matrix` data.
+
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+
+use crate::{
+    data_structures::matrix::Matrix,
+    networks::{network::Network, serialization::LayerRecord},
+    onnx::protobuf::Writer,
+};
+
+pub const ONNX_IR_VERSION: i64 = 8;
+pub const ONNX_OPSET_VERSION: i64 = 13;
+pub const INPUT_NAME: &str = "input";
+pub const OUTPUT_NAME: &str = "output";
+
+// Field numbers and enum values from onnx.proto.
+pub(crate) mod fields {
+    pub const MODEL_IR_VERSION: u32 = 1;
+    pub const MODEL_PRODUCER_NAME: u32 = 2;
+    pub const MODEL_PRODUCER_VERSION: u32 = 3;
+    pub const MODEL_GRAPH: u32 = 7;
+    pub const MODEL_OPSET_IMPORT: u32 = 8;
+    pub const OPSET_DOMAIN: u32 = 1;
+    pub const OPSET_VERSION: u32 = 2;
+
+    pub const GRAPH_NODE: u32 = 1;
+    pub const GRAPH_NAME: u32 = 2;
+    pub const GRAPH_INITIALIZER: u32 = 5;
+    pub const GRAPH_INPUT: u32 = 11;
+    pub const GRAPH_OUTPUT: u32 = 12;
+
+    pub const NODE_INPUT: u32 = 1;
+    pub const NODE_OUTPUT: u32 = 2;
+    pub const NODE_NAME: u32 = 3;
+    pub const NODE_OP_TYPE: u32 = 4;
+    pub const NODE_ATTRIBUTE: u32 = 5;
+
+    pub const ATTRIBUTE_NAME: u32 = 1;
+    pub const ATTRIBUTE_F: u32 = 2;
+    pub const ATTRIBUTE_I: u32 = 3;
+    pub const ATTRIBUTE_TYPE: u32 = 20;
+    pub const ATTRIBUTE_TYPE_FLOAT: i64 = 1;
+    pub const ATTRIBUTE_TYPE_INT: i64 = 2;
+
+    pub const TENSOR_DIMS: u32 = 1;
+    pub const TENSOR_DATA_TYPE: u32 = 2;
+    pub const TENSOR_FLOAT_DATA: u32 = 4;
+    pub const TENSOR_NAME: u32 = 8;
+    pub const TENSOR_RAW_DATA: u32 = 9;
+    pub const TENSOR_TYPE_FLOAT: i64 = 1;
+
+    pub const VALUE_INFO_NAME: u32 = 1;
+    pub const VALUE_INFO_TYPE: u32 = 2;
+    pub const TYPE_TENSOR: u32 = 1;
+    pub const TYPE_TENSOR_ELEM_TYPE: u32 = 1;
+    pub const TYPE_TENSOR_SHAPE: u32 = 2;
+    pub const SHAPE_DIM: u32 = 1;
+    pub const DIM_VALUE: u32 = 1;
+    pub const DIM_PARAM: u32 = 2;
+}
+
+use fields::*;
+
+enum Attribute {
+    Int(i64),
+}
+
+struct Node {
+    op_type: &'static str,
+    name: String,
+    inputs: Vec<String>,
+    output: String,
+    attributes: Vec<(&'static str, Attribute)>,
+}
+
+/// Writes a float tensor with the given row-major dimensions.
+fn write_tensor(w: &mut Writer, name: &str, dims: &[usize], row_major: &[f32]) {
+    for &dim in dims {
+        w.int64(TENSOR_DIMS, dim as i64);
+    }
+    w.int64(TENSOR_DATA_TYPE, TENSOR_TYPE_FLOAT);
+    w.string(TENSOR_NAME, name);
+    let raw: Vec<u8> = row_major.iter().flat_map(|v| v.to_le_bytes()).collect();
+    w.bytes(TENSOR_RAW_DATA, &raw);
+}
+
+/// Row-major copy of a column-major matrix.
+fn row_major(matrix: &Matrix) -> Vec<f32> {
+    (0..matrix.rows)
+        .flat_map(|r| (0..matrix.cols).map(move |c| matrix.get(r, c)))
+        .collect()
+}
+
+fn write_value_info(w: &mut Writer, name: &str, features: usize) {
+    w.string(VALUE_INFO_NAME, name);
+    w.message(VALUE_INFO_TYPE, |t| {
+        t.message(TYPE_TENSOR, |tensor| {
+            tensor.int64(TYPE_TENSOR_ELEM_TYPE, TENSOR_TYPE_FLOAT);
+            tensor.message(TYPE_TENSOR_SHAPE, |shape| {
+                shape.message(SHAPE_DIM, |dim| {
+                    dim.string(DIM_PARAM, "batch");
+                });
+                shape.message(SHAPE_DIM, |dim| {
+                    dim.int64(DIM_VALUE, features as i64);
+                });
+            });
+        });
+    });
+}
+
+/// Serializes the network as an ONNX `ModelProto`.
+pub fn to_onnx_bytes(network: &Network) -> anyhow::Result<Vec<u8>> {
+    let records = network.layer_records()?;
+
+    let dense_sizes = || {
+        records.iter().filter_map(|record| match record {
+            LayerRecord::Dense {
+                input_size,
+                output_size,
+                ..
+            } => Some((*input_size, *output_size)),
+            _ => None,
+        })
+    };
+    let input_size = dense_sizes()
+        .next()
+        .map(|(input_size, _)| input_size)
+        .ok_or_else(|| anyhow!("cannot export a network without dense layers"))?;
+    let output_size = dense_sizes().next_back().unwrap().1;
+
+    let mut nodes = Vec::new();
+    let mut initializers: Vec<(String, Vec<usize>, Vec<f32>)> = Vec::new();
+    let mut current = INPUT_NAME.to_string();
+    for (i, record) in records.iter().enumerate() {
+        let output = if i + 1 == records.len() {
+            OUTPUT_NAME.to_string()
+        } else {
+            format!("layer_{}_out", i)
+        };
+        let node = match record {
+            LayerRecord::Dense {
+                input_size,
+                output_size,
+                weights,
+                biases,
+            } => {
+                let (w_name, b_name) = (format!("dense_{}.weight", i), format!("dense_{}.bias", i));
+                initializers.push((
+                    w_name.clone(),
+                    vec![*output_size, *input_size],
+                    row_major(weights),
+                ));
+                initializers.push((b_name.clone(), vec![*output_size], row_major(biases)));
+                Node {
+                    op_type: "Gemm",
+                    name: format!("dense_{}", i),
+                    inputs: vec![current, w_name, b_name],
+                    output,
+                    attributes: vec![("transB", Attribute::Int(1))],
+                }
+            }
+            LayerRecord::ReLU => Node {
+                op_type: "Relu",
+                name: format!("relu_{}", i),
+                inputs: vec![current],
+                output,
+                attributes: Vec::new(),
+            },
+            LayerRecord::Softmax => Node {
+                op_type: "Softmax",
+                name: format!("softmax_{}", i),
+                inputs: vec![current],
+                output,
+                attributes: vec![("axis", Attribute::Int(1))],
+            },
+        };
+        current = node.output.clone();
+        nodes.push(node);
+    }
+
+    let mut model = Writer::new();
+    model
+        .int64(MODEL_IR_VERSION, ONNX_IR_VERSION)
+        .string(MODEL_PRODUCER_NAME, env!("CARGO_PKG_NAME"))
+        .string(MODEL_PRODUCER_VERSION, env!("CARGO_PKG_VERSION"))
+        .message(MODEL_OPSET_IMPORT, |opset| {
+            opset.string(OPSET_DOMAIN, "");
+            opset.int64(OPSET_VERSION, ONNX_OPSET_VERSION);
+        })
+        .message(MODEL_GRAPH, |graph| {
+            for node in &nodes {
+                graph.message(GRAPH_NODE, |n| {
+                    for input in &node.inputs {
+                        n.string(NODE_INPUT, input);
+                    }
+                    n.string(NODE_OUTPUT, &node.output);
+                    n.string(NODE_NAME, &node.name);
+                    n.string(NODE_OP_TYPE, node.op_type);
+                    for (name, attribute) in &node.attributes {
+                        n.message(NODE_ATTRIBUTE, |a| {
+                            a.string(ATTRIBUTE_NAME, name);
+                            match attribute {
+                                Attribute::Int(value) => {
+                                    a.int64(ATTRIBUTE_I, *value);
+                                    a.int64(ATTRIBUTE_TYPE, ATTRIBUTE_TYPE_INT);
+                                }
+                            }
+                        });
+                    }
+                });
+            }
+            graph.string(GRAPH_NAME, env!("CARGO_PKG_NAME"));
+            for (name, dims, data) in &initializers {
+                graph.message(GRAPH_INITIALIZER, |t| write_tensor(t, name, dims, data));
+            }
+            graph.message(GRAPH_INPUT, |v| write_value_info(v, INPUT_NAME, input_size));
+            graph.message(GRAPH_OUTPUT, |v| {
+                write_value_info(v, OUTPUT_NAME, output_size)
+            });
+        });
+
+    Ok(model.into_bytes())
+}
+
+impl Network {
+    /// Writes the network as an ONNX model that takes `[batch, input_size]` and returns
+    /// `[batch, output_size]` (samples as rows, unlike `forward`).
+    pub fn export_onnx<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let bytes = to_onnx_bytes(self)?;
+        std::fs::write(path, bytes)
+            .with_context(|| format!("failed to write ONNX model to {}", path.display()))?;
+        log::info!("Exported ONNX model to {}", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod export;
+pub mod onnx_tests;
+pub mod protobuf;
+pub mod reader;
@@ -0,0 +1,194 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        data_structures::matrix::Matrix,
+        layers::{
+            dense::{ConfigDenseLayer, DenseLayer},
+            relu::ReLULayer,
+            softmax::Softmax,
+        },
+        networks::network::Network,
+        onnx::{
+            export::{ONNX_OPSET_VERSION, fields::*, to_onnx_bytes},
+            protobuf::{Writer, fields},
+            reader::{AttributeValue, OnnxModel, OnnxNode, OnnxTensor, OnnxValueInfo},
+        },
+    };
+
+    fn mlp() -> Network {
+        let config = ConfigDenseLayer::default();
+        let mut net = Network::new();
+        net.add_layer(DenseLayer::new(4, 5, &config));
+        net.add_layer(ReLULayer::new());
+        net.add_layer(DenseLayer::new(5, 3, &config));
+        net.add_layer(Softmax::new());
+        net
+    }
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let mut w = Writer::new();
+        w.int64(1, 300).string(2, "abc").float(3, 1.5);
+        w.message(4, |nested| {
+            nested.int64(1, -1);
+        });
+        let bytes = w.into_bytes();
+
+        let decoded = fields(&bytes).unwrap();
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(decoded[0].1.as_u64().unwrap(), 300);
+        assert_eq!(decoded[1].1.as_str().unwrap(), "abc");
+        assert_eq!(decoded[2].1.as_f32().unwrap(), 1.5);
+        let nested = fields(decoded[3].1.as_bytes().unwrap()).unwrap();
+        assert_eq!(nested[0].1.as_i64().unwrap(), -1);
+
+        assert!(fields(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let mut net = mlp();
+        let model = OnnxModel::parse(&to_onnx_bytes(&net).unwrap()).unwrap();
+
+        assert_eq!(model.opset_version, Some(ONNX_OPSET_VERSION));
+        let ops: Vec<&str> = model.nodes.iter().map(|n| n.op_type.as_str()).collect();
+        assert_eq!(ops, vec!["Gemm", "Relu", "Gemm", "Softmax"]);
+        assert_eq!(
+            model.nodes[0].attribute("transB"),
+            Some(AttributeValue::Int(1))
+        );
+        assert_eq!(model.inputs.len(), 1);
+        assert_eq!(model.inputs[0].dims, vec![None, Some(4)]);
+        assert_eq!(model.outputs[0].dims, vec![None, Some(3)]);
+
+        // Initializers are row-major [output_size, input_size].
+        let weights = model.initializer("dense_0.weight").unwrap();
+        assert_eq!(weights.dims, vec![5, 4]);
+        let expected = net.layers[0].get_weights().unwrap();
+        assert_eq!(weights.data[4 + 2], expected.get(1, 2));
+
+        let x = Matrix::new_seeded_random(4, 6, 3);
+        let expected = net.forward(&x);
+        let actual = model.run(&x).unwrap();
+        assert_eq!((actual.rows, actual.cols), (3, 6));
+        for (a, b) in expected.data.iter().zip(actual.data.iter()) {
+            assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_export_onnx_writes_file() {
+        let path = std::env::temp_dir().join("neural_networks_export.onnx");
+        mlp().export_onnx(&path).unwrap();
+        let model = OnnxModel::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(model.unwrap().initializers.len(), 4);
+    }
+
+    // A graph computing `softmax(x + bias)` for inputs of `features` features.
+    fn softmax_graph(features: usize, bias: OnnxTensor) -> OnnxModel {
+        let node = |op_type: &str, inputs: &[&str], output: &str| OnnxNode {
+            name: op_type.to_lowercase(),
+            op_type: op_type.to_string(),
+            inputs: inputs.iter().map(|name| name.to_string()).collect(),
+            outputs: vec![output.to_string()],
+            attributes: Vec::new(),
+        };
+        let value = |name: &str| OnnxValueInfo {
+            name: name.to_string(),
+            dims: vec![None, Some(features)],
+        };
+        OnnxModel {
+            ir_version: 8,
+            opset_version: Some(ONNX_OPSET_VERSION),
+            producer_name: String::new(),
+            nodes: vec![
+                node("Add", &["x", "bias"], "sum"),
+                node("Softmax", &["sum"], "y"),
+            ],
+            initializers: vec![bias],
+            inputs: vec![value("x")],
+            outputs: vec![value("y")],
+        }
+    }
+
+    #[test]
+    fn test_run_rejects_malformed_dims() {
+        let bias = |dims: Vec<usize>, data: Vec<f32>| OnnxTensor {
+            name: "bias".to_string(),
+            dims,
+            data,
+        };
+
+        let model = softmax_graph(2, bias(vec![2], vec![0.5, -0.5]));
+        assert!(model.run(&Matrix::new_seeded_random(2, 3, 1)).is_ok());
+
+        let empty = softmax_graph(0, bias(vec![0], Vec::new()));
+        assert!(empty.run(&Matrix::new(0, 3)).is_err());
+
+        let malformed = softmax_graph(2, bias(vec![2], vec![0.5]));
+        let err = malformed.run(&Matrix::new(2, 3)).err().unwrap();
+        assert!(format!("{:#}", err).contains("'bias' has 1 values"));
+    }
+
+    /// A model whose graph has one float initializer with the given raw dims.
+    fn model_with_initializer(dims: &[i64]) -> Vec<u8> {
+        let mut w = Writer::new();
+        w.message(MODEL_GRAPH, |graph| {
+            graph.message(GRAPH_INITIALIZER, |tensor| {
+                for &dim in dims {
+                    tensor.int64(TENSOR_DIMS, dim);
+                }
+                tensor.int64(TENSOR_DATA_TYPE, TENSOR_TYPE_FLOAT);
+                tensor.string(TENSOR_NAME, "w");
+            });
+        });
+        w.into_bytes()
+    }
+
+    #[test]
+    fn test_parse_rejects_negative_and_overflowing_dims() {
+        assert!(OnnxModel::parse(&model_with_initializer(&[0, 2])).is_ok());
+
+        let err = OnnxModel::parse(&model_with_initializer(&[-1, 2]))
+            .err()
+            .unwrap();
+        assert!(format!("{:#}", err).contains("invalid dimension -1"));
+
+        let err = OnnxModel::parse(&model_with_initializer(&[1 << 62, 4]))
+            .err()
+            .unwrap();
+        assert!(format!("{:#}", err).contains("overflow"));
+
+        let mut w = Writer::new();
+        w.message(MODEL_GRAPH, |graph| {
+            graph.message(GRAPH_INPUT, |info| {
+                info.string(VALUE_INFO_NAME, "x");
+                info.message(VALUE_INFO_TYPE, |t| {
+                    t.message(TYPE_TENSOR, |tensor| {
+                        tensor.message(TYPE_TENSOR_SHAPE, |shape| {
+                            shape.message(SHAPE_DIM, |dim| {
+                                dim.int64(DIM_VALUE, -3);
+                            });
+                        });
+                    });
+                });
+            });
+        });
+        assert!(OnnxModel::parse(&w.into_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_run_rejects_overflowing_dims() {
+        let bias = OnnxTensor {
+            name: "bias".to_string(),
+            dims: vec![usize::MAX, 2],
+            data: vec![0.5, -0.5],
+        };
+        let err = softmax_graph(2, bias)
+            .run(&Matrix::new(2, 3))
+            .err()
+            .unwrap();
+        assert!(format!("{:#}", err).contains("overflow"));
+    }
+}
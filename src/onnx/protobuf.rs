@@ -0,0 +1,180 @@
+//! Minimal protobuf wire-format encoding and decoding, just enough for ONNX models.
+
+use anyhow::anyhow;
+
+pub const WIRE_VARINT: u8 = 0;
+pub const WIRE_FIXED64: u8 = 1;
+pub const WIRE_LEN: u8 = 2;
+pub const WIRE_FIXED32: u8 = 5;
+
+/// Appends fields to a protobuf message.
+#[derive(Default)]
+pub struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Writer { buf: Vec::new() }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn tag(&mut self, field: u32, wire_type: u8) {
+        self.varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    pub fn int64(&mut self, field: u32, value: i64) -> &mut Self {
+        self.tag(field, WIRE_VARINT);
+        self.varint(value as u64);
+        self
+    }
+
+    pub fn float(&mut self, field: u32, value: f32) -> &mut Self {
+        self.tag(field, WIRE_FIXED32);
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn bytes(&mut self, field: u32, value: &[u8]) -> &mut Self {
+        self.tag(field, WIRE_LEN);
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value);
+        self
+    }
+
+    pub fn string(&mut self, field: u32, value: &str) -> &mut Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    /// Writes a nested message built by `build`.
+    pub fn message(&mut self, field: u32, build: impl FnOnce(&mut Writer)) -> &mut Self {
+        let mut nested = Writer::new();
+        build(&mut nested);
+        self.bytes(field, &nested.buf)
+    }
+}
+
+/// Value of a single decoded field.
+#[derive(Debug, Clone, Copy)]
+pub enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32(u32),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> Value<'a> {
+    pub fn as_u64(&self) -> anyhow::Result<u64> {
+        match *self {
+            Value::Varint(v) | Value::Fixed64(v) => Ok(v),
+            Value::Fixed32(v) => Ok(v as u64),
+            Value::Bytes(_) => Err(anyhow!("expected a number, found a length-delimited field")),
+        }
+    }
+
+    pub fn as_i64(&self) -> anyhow::Result<i64> {
+        self.as_u64().map(|v| v as i64)
+    }
+
+    pub fn as_f32(&self) -> anyhow::Result<f32> {
+        match *self {
+            Value::Fixed32(v) => Ok(f32::from_bits(v)),
+            _ => Err(anyhow!("expected a 32-bit float")),
+        }
+    }
+
+    pub fn as_bytes(&self) -> anyhow::Result<&'a [u8]> {
+        match *self {
+            Value::Bytes(bytes) => Ok(bytes),
+            _ => Err(anyhow!("expected a length-delimited field")),
+        }
+    }
+
+    pub fn as_str(&self) -> anyhow::Result<&'a str> {
+        Ok(std::str::from_utf8(self.as_bytes()?)?)
+    }
+
+    /// Elements of a repeated integer field, which may be packed or not.
+    pub fn as_packed_i64(&self) -> anyhow::Result<Vec<i64>> {
+        match *self {
+            Value::Bytes(mut bytes) => {
+                let mut values = Vec::new();
+                while !bytes.is_empty() {
+                    values.push(read_varint(&mut bytes)? as i64);
+                }
+                Ok(values)
+            }
+            _ => Ok(vec![self.as_i64()?]),
+        }
+    }
+}
+
+fn read_varint(bytes: &mut &[u8]) -> anyhow::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or_else(|| anyhow!("truncated varint"))?;
+        *bytes = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow!("varint is longer than 10 bytes"))
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> anyhow::Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(anyhow!(
+            "truncated field: need {} bytes, {} left",
+            len,
+            bytes.len()
+        ));
+    }
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(head)
+}
+
+/// Splits a message into `(field number, value)` pairs, in the order they appear.
+pub fn fields(mut bytes: &[u8]) -> anyhow::Result<Vec<(u32, Value<'_>)>> {
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let field = (key >> 3) as u32;
+        let value = match (key & 0x7) as u8 {
+            WIRE_VARINT => Value::Varint(read_varint(&mut bytes)?),
+            WIRE_FIXED64 => {
+                Value::Fixed64(u64::from_le_bytes(take(&mut bytes, 8)?.try_into().unwrap()))
+            }
+            WIRE_LEN => {
+                let len = read_varint(&mut bytes)? as usize;
+                Value::Bytes(take(&mut bytes, len)?)
+            }
+            WIRE_FIXED32 => {
+                Value::Fixed32(u32::from_le_bytes(take(&mut bytes, 4)?.try_into().unwrap()))
+            }
+            other => {
+                return Err(anyhow!(
+                    "unsupported wire type {} in field {}",
+                    other,
+                    field
+                ));
+            }
+        };
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
@@ -0,0 +1,524 @@
+//! Minimal ONNX reader: parses the parts of a `ModelProto` needed for simple MLPs and
+//! evaluates graphs made of `Gemm`, `MatMul`, `Add`, `Relu` and `Softmax` nodes.
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, anyhow};
+
+use crate::{
+    data_structures::matrix::Matrix,
+    onnx::{
+        export::fields::*,
+        protobuf::{Value, fields},
+    },
+};
+
+/// A float tensor with row-major `data`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnnxTensor {
+    pub name: String,
+    pub dims: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeValue {
+    Int(i64),
+    Float(f32),
+}
+
+#[derive(Debug, Clone)]
+pub struct OnnxNode {
+    pub name: String,
+    pub op_type: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub attributes: Vec<(String, AttributeValue)>,
+}
+
+/// Name and shape of a graph input or output, `None` for symbolic dimensions.
+#[derive(Debug, Clone)]
+pub struct OnnxValueInfo {
+    pub name: String,
+    pub dims: Vec<Option<usize>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OnnxModel {
+    pub ir_version: i64,
+    pub opset_version: Option<i64>,
+    pub producer_name: String,
+    pub nodes: Vec<OnnxNode>,
+    pub initializers: Vec<OnnxTensor>,
+    pub inputs: Vec<OnnxValueInfo>,
+    pub outputs: Vec<OnnxValueInfo>,
+}
+
+impl OnnxNode {
+    pub fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| *value)
+    }
+
+    pub(crate) fn int_attribute(&self, name: &str, default: i64) -> i64 {
+        match self.attribute(name) {
+            Some(AttributeValue::Int(value)) => value,
+            _ => default,
+        }
+    }
+
+    pub(crate) fn float_attribute(&self, name: &str, default: f32) -> f32 {
+        match self.attribute(name) {
+            Some(AttributeValue::Float(value)) => value,
+            _ => default,
+        }
+    }
+}
+
+fn dim(value: i64) -> anyhow::Result<usize> {
+    usize::try_from(value).map_err(|_| anyhow!("invalid dimension {}", value))
+}
+
+/// The number of elements in a tensor with the given dims.
+fn element_count(dims: &[usize]) -> anyhow::Result<usize> {
+    dims.iter()
+        .try_fold(1usize, |count, &d| count.checked_mul(d))
+        .ok_or_else(|| anyhow!("dims {:?} overflow the element count", dims))
+}
+
+fn parse_tensor(bytes: &[u8]) -> anyhow::Result<OnnxTensor> {
+    let mut tensor = OnnxTensor {
+        name: String::new(),
+        dims: Vec::new(),
+        data: Vec::new(),
+    };
+    let mut data_type = None;
+    for (field, value) in fields(bytes)? {
+        match field {
+            TENSOR_DIMS => {
+                for d in value.as_packed_i64()? {
+                    tensor.dims.push(dim(d)?);
+                }
+            }
+            TENSOR_DATA_TYPE => data_type = Some(value.as_i64()?),
+            TENSOR_NAME => tensor.name = value.as_str()?.to_string(),
+            TENSOR_RAW_DATA => {
+                let raw = value.as_bytes()?;
+                if !raw.len().is_multiple_of(4) {
+                    return Err(anyhow!(
+                        "raw_data length {} is not a multiple of 4",
+                        raw.len()
+                    ));
+                }
+                tensor.data.extend(
+                    raw.chunks_exact(4)
+                        .map(|b| f32::from_le_bytes(b.try_into().unwrap())),
+                );
+            }
+            TENSOR_FLOAT_DATA => match value {
+                Value::Bytes(packed) => tensor.data.extend(
+                    packed
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes(b.try_into().unwrap())),
+                ),
+                _ => tensor.data.push(value.as_f32()?),
+            },
+            _ => {}
+        }
+    }
+
+    if data_type != Some(TENSOR_TYPE_FLOAT) {
+        return Err(anyhow!(
+            "tensor '{}' has data type {:?}, only float (1) is supported",
+            tensor.name,
+            data_type
+        ));
+    }
+    let expected =
+        element_count(&tensor.dims).with_context(|| format!("tensor '{}'", tensor.name))?;
+    if tensor.data.len() != expected {
+        return Err(anyhow!(
+            "tensor '{}' has {} values, its dims {:?} require {}",
+            tensor.name,
+            tensor.data.len(),
+            tensor.dims,
+            expected
+        ));
+    }
+    Ok(tensor)
+}
+
+fn parse_attribute(bytes: &[u8]) -> anyhow::Result<(String, Option<AttributeValue>)> {
+    let (mut name, mut int, mut float, mut kind) = (String::new(), None, None, None);
+    for (field, value) in fields(bytes)? {
+        match field {
+            ATTRIBUTE_NAME => name = value.as_str()?.to_string(),
+            ATTRIBUTE_I => int = Some(value.as_i64()?),
+            ATTRIBUTE_F => float = Some(value.as_f32()?),
+            ATTRIBUTE_TYPE => kind = Some(value.as_i64()?),
+            _ => {}
+        }
+    }
+    let value = match kind {
+        Some(ATTRIBUTE_TYPE_INT) => int.map(AttributeValue::Int),
+        Some(ATTRIBUTE_TYPE_FLOAT) => float.map(AttributeValue::Float),
+        // Other attribute types (strings, tensors, graphs, ...) are not needed here.
+        _ => None,
+    };
+    Ok((name, value))
+}
+
+fn parse_node(bytes: &[u8]) -> anyhow::Result<OnnxNode> {
+    let mut node = OnnxNode {
+        name: String::new(),
+        op_type: String::new(),
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        attributes: Vec::new(),
+    };
+    for (field, value) in fields(bytes)? {
+        match field {
+            NODE_INPUT => node.inputs.push(value.as_str()?.to_string()),
+            NODE_OUTPUT => node.outputs.push(value.as_str()?.to_string()),
+            NODE_NAME => node.name = value.as_str()?.to_string(),
+            NODE_OP_TYPE => node.op_type = value.as_str()?.to_string(),
+            NODE_ATTRIBUTE => {
+                if let (name, Some(attribute)) = parse_attribute(value.as_bytes()?)? {
+                    node.attributes.push((name, attribute));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(node)
+}
+
+fn parse_value_info(bytes: &[u8]) -> anyhow::Result<OnnxValueInfo> {
+    let mut info = OnnxValueInfo {
+        name: String::new(),
+        dims: Vec::new(),
+    };
+    for (field, value) in fields(bytes)? {
+        match field {
+            VALUE_INFO_NAME => info.name = value.as_str()?.to_string(),
+            VALUE_INFO_TYPE => {
+                for (field, value) in fields(value.as_bytes()?)? {
+                    if field != TYPE_TENSOR {
+                        continue;
+                    }
+                    for (field, value) in fields(value.as_bytes()?)? {
+                        if field != TYPE_TENSOR_SHAPE {
+                            continue;
+                        }
+                        for (field, value) in fields(value.as_bytes()?)? {
+                            if field != SHAPE_DIM {
+                                continue;
+                            }
+                            let mut size = None;
+                            for (field, value) in fields(value.as_bytes()?)? {
+                                if field == DIM_VALUE {
+                                    size = Some(dim(value.as_i64()?)?);
+                                }
+                            }
+                            info.dims.push(size);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+impl OnnxModel {
+    pub fn parse(bytes: &[u8]) -> anyhow::Result<OnnxModel> {
+        let mut model = OnnxModel {
+            ir_version: 0,
+            opset_version: None,
+            producer_name: String::new(),
+            nodes: Vec::new(),
+            initializers: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        };
+        let mut graph = None;
+        for (field, value) in fields(bytes)? {
+            match field {
+                MODEL_IR_VERSION => model.ir_version = value.as_i64()?,
+                MODEL_PRODUCER_NAME => model.producer_name = value.as_str()?.to_string(),
+                MODEL_GRAPH => graph = Some(value.as_bytes()?),
+                MODEL_OPSET_IMPORT => {
+                    let (mut domain, mut version) = ("", None);
+                    for (field, value) in fields(value.as_bytes()?)? {
+                        match field {
+                            OPSET_DOMAIN => domain = value.as_str()?,
+                            OPSET_VERSION => version = Some(value.as_i64()?),
+                            _ => {}
+                        }
+                    }
+                    if domain.is_empty() || domain == "ai.onnx" {
+                        model.opset_version = version;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let graph = graph.ok_or_else(|| anyhow!("ONNX model has no graph"))?;
+        for (field, value) in fields(graph)? {
+            match field {
+                GRAPH_NODE => model.nodes.push(parse_node(value.as_bytes()?)?),
+                GRAPH_INITIALIZER => model
+                    .initializers
+                    .push(parse_tensor(value.as_bytes()?).context("invalid initializer")?),
+                GRAPH_INPUT => model.inputs.push(parse_value_info(value.as_bytes()?)?),
+                GRAPH_OUTPUT => model.outputs.push(parse_value_info(value.as_bytes()?)?),
+                _ => {}
+            }
+        }
+
+        // Older exporters also list the initializers as graph inputs.
+        let model_inputs = model
+            .inputs
+            .iter()
+            .filter(|input| model.initializer(&input.name).is_none())
+            .cloned()
+            .collect();
+        model.inputs = model_inputs;
+        Ok(model)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> anyhow::Result<OnnxModel> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read ONNX model from {}", path.display()))?;
+        OnnxModel::parse(&bytes)
+            .with_context(|| format!("failed to parse ONNX model {}", path.display()))
+    }
+
+    pub fn initializer(&self, name: &str) -> Option<&OnnxTensor> {
+        self.initializers.iter().find(|tensor| tensor.name == name)
+    }
+
+    /// Evaluates the graph on `input` of shape `(features, batch)`, like `Network::forward`.
+    /// The graph must have a single input and output of shape `[batch, features]`.
+    pub fn run(&self, input: &Matrix) -> anyhow::Result<Matrix> {
+        let (graph_input, graph_output) = match (self.inputs.as_slice(), self.outputs.as_slice()) {
+            ([input], [output]) => (input, output),
+            _ => {
+                return Err(anyhow!(
+                    "only graphs with one input and one output are supported"
+                ));
+            }
+        };
+
+        let mut values: HashMap<&str, OnnxTensor> = self
+            .initializers
+            .iter()
+            .map(|tensor| (tensor.name.as_str(), tensor.clone()))
+            .collect();
+        // A column-major (features, batch) matrix has the layout of a row-major [batch, features].
+        values.insert(
+            graph_input.name.as_str(),
+            OnnxTensor {
+                name: graph_input.name.clone(),
+                dims: vec![input.cols, input.rows],
+                data: input.data.clone(),
+            },
+        );
+
+        for node in &self.nodes {
+            let inputs = node
+                .inputs
+                .iter()
+                .map(|name| {
+                    values.get(name.as_str()).ok_or_else(|| {
+                        anyhow!("node '{}' uses unknown value '{}'", node.name, name)
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let output = evaluate(node, &inputs).with_context(|| {
+                format!("failed to evaluate {} node '{}'", node.op_type, node.name)
+            })?;
+            let output_name = node
+                .outputs
+                .first()
+                .ok_or_else(|| anyhow!("node '{}' has no output", node.name))?;
+            values.insert(output_name.as_str(), output);
+        }
+
+        let output = values
+            .remove(graph_output.name.as_str())
+            .ok_or_else(|| anyhow!("graph output '{}' is never computed", graph_output.name))?;
+        let [batch, features] = output.dims[..] else {
+            return Err(anyhow!(
+                "graph output has dims {:?}, expected 2",
+                output.dims
+            ));
+        };
+        Ok(Matrix {
+            rows: features,
+            cols: batch,
+            data: output.data,
+        })
+    }
+}
+
+fn matrix_dims(tensor: &OnnxTensor) -> anyhow::Result<(usize, usize)> {
+    match tensor.dims[..] {
+        [rows, cols] => Ok((rows, cols)),
+        _ => Err(anyhow!(
+            "'{}' has dims {:?}, expected a matrix",
+            tensor.name,
+            tensor.dims
+        )),
+    }
+}
+
+/// Row-major matrix product of `a` (optionally transposed) and `b` (optionally transposed).
+fn matmul(
+    a: &OnnxTensor,
+    trans_a: bool,
+    b: &OnnxTensor,
+    trans_b: bool,
+) -> anyhow::Result<OnnxTensor> {
+    let (ar, ac) = matrix_dims(a)?;
+    let (br, bc) = matrix_dims(b)?;
+    let (m, k) = if trans_a { (ac, ar) } else { (ar, ac) };
+    let (k2, n) = if trans_b { (bc, br) } else { (br, bc) };
+    if k != k2 {
+        return Err(anyhow!("cannot multiply [{}, {}] by [{}, {}]", m, k, k2, n));
+    }
+    let a_at = |i: usize, j: usize| {
+        if trans_a {
+            a.data[j * ac + i]
+        } else {
+            a.data[i * ac + j]
+        }
+    };
+    let b_at = |i: usize, j: usize| {
+        if trans_b {
+            b.data[j * bc + i]
+        } else {
+            b.data[i * bc + j]
+        }
+    };
+
+    let mut data = vec![0.0; m * n];
+    for i in 0..m {
+        for j in 0..n {
+            data[i * n + j] = (0..k).map(|p| a_at(i, p) * b_at(p, j)).sum();
+        }
+    }
+    Ok(OnnxTensor {
+        name: String::new(),
+        dims: vec![m, n],
+        data,
+    })
+}
+
+/// `a + scale * b`, where `b` is broadcast along the rows if it is a vector.
+fn add(a: &OnnxTensor, b: &OnnxTensor, scale: f32) -> anyhow::Result<OnnxTensor> {
+    let (rows, cols) = matrix_dims(a)?;
+    let broadcast = match b.dims[..] {
+        [n] | [1, n] if n == cols => true,
+        [r, c] if (r, c) == (rows, cols) => false,
+        _ => {
+            return Err(anyhow!(
+                "cannot broadcast {:?} to [{}, {}]",
+                b.dims,
+                rows,
+                cols
+            ));
+        }
+    };
+    let mut out = a.clone();
+    for (i, v) in out.data.iter_mut().enumerate() {
+        let other = if broadcast {
+            b.data[i % cols]
+        } else {
+            b.data[i]
+        };
+        *v += scale * other;
+    }
+    Ok(out)
+}
+
+fn evaluate(node: &OnnxNode, inputs: &[&OnnxTensor]) -> anyhow::Result<OnnxTensor> {
+    let arity = |n: usize| {
+        if inputs.len() < n {
+            Err(anyhow!("expected {} inputs, got {}", n, inputs.len()))
+        } else {
+            Ok(())
+        }
+    };
+
+    for input in inputs {
+        let expected = element_count(&input.dims).with_context(|| format!("'{}'", input.name))?;
+        if input.data.len() != expected {
+            return Err(anyhow!(
+                "'{}' has {} values, its dims {:?} require {}",
+                input.name,
+                input.data.len(),
+                input.dims,
+                expected
+            ));
+        }
+    }
+
+    match node.op_type.as_str() {
+        "Gemm" => {
+            arity(2)?;
+            let alpha = node.float_attribute("alpha", 1.0);
+            let beta = node.float_attribute("beta", 1.0);
+            let mut out = matmul(
+                inputs[0],
+                node.int_attribute("transA", 0) != 0,
+                inputs[1],
+                node.int_attribute("transB", 0) != 0,
+            )?;
+            out.data.iter_mut().for_each(|v| *v *= alpha);
+            match inputs.get(2) {
+                Some(c) => add(&out, c, beta),
+                None => Ok(out),
+            }
+        }
+        "MatMul" => {
+            arity(2)?;
+            matmul(inputs[0], false, inputs[1], false)
+        }
+        "Add" => {
+            arity(2)?;
+            add(inputs[0], inputs[1], 1.0)
+        }
+        "Relu" => {
+            arity(1)?;
+            let mut out = inputs[0].clone();
+            out.data.iter_mut().for_each(|v| *v = v.max(0.0));
+            Ok(out)
+        }
+        "Softmax" => {
+            arity(1)?;
+            let (_, cols) = matrix_dims(inputs[0])?;
+            let axis = node.int_attribute("axis", -1);
+            if axis != 1 && axis != -1 {
+                return Err(anyhow!("only softmax over the last axis is supported"));
+            }
+            if cols == 0 {
+                return Err(anyhow!("cannot take the softmax over an empty axis"));
+            }
+            let mut out = inputs[0].clone();
+            for row in out.data.chunks_mut(cols) {
+                let max = row.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+                row.iter_mut().for_each(|v| *v = (*v - max).exp());
+                let sum: f32 = row.iter().sum();
+                row.iter_mut().for_each(|v| *v /= sum);
+            }
+            Ok(out)
+        }
+        other => Err(anyhow!("unsupported operator '{}'", other)),
+    }
+}
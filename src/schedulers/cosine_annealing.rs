@@ -0,0 +1,40 @@
+use crate::{Dtype, schedulers::LrScheduler};
+
+const PI: Dtype = core::f64::consts::PI as Dtype;
+
+/// Cosine annealing with warm restarts (SGDR, Loshchilov & Hutter).
+///
+/// The learning rate follows half a cosine from `base_lr` down to `min_lr` over `period` steps,
+/// then restarts. Each following period is `period_mult` times longer than the previous one.
+pub struct CosineAnnealingWarmRestarts {
+    pub period: usize,
+    pub period_mult: usize,
+    pub min_lr: Dtype,
+}
+
+impl CosineAnnealingWarmRestarts {
+    pub fn new(period: usize, period_mult: usize, min_lr: Dtype) -> Self {
+        assert!(period > 0, "period must be positive");
+        assert!(period_mult > 0, "period_mult must be positive");
+        CosineAnnealingWarmRestarts {
+            period,
+            period_mult,
+            min_lr,
+        }
+    }
+}
+
+impl LrScheduler for CosineAnnealingWarmRestarts {
+    fn learning_rate(&self, base_lr: Dtype, step: usize) -> Dtype {
+        // Find the position inside the current restart period.
+        let mut t_cur = step;
+        let mut t_i = self.period;
+        while t_cur >= t_i {
+            t_cur -= t_i;
+            t_i *= self.period_mult;
+        }
+
+        let progress = t_cur as Dtype / t_i as Dtype;
+        self.min_lr + (base_lr - self.min_lr) * (1.0 + (PI * progress).cos()) / 2.0
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{Dtype, schedulers::LrScheduler};
+
+/// Multiplies the learning rate by `gamma` every step: `lr = base_lr * gamma^step`.
+pub struct ExponentialDecay {
+    pub gamma: Dtype,
+}
+
+impl ExponentialDecay {
+    pub fn new(gamma: Dtype) -> Self {
+        ExponentialDecay { gamma }
+    }
+}
+
+impl LrScheduler for ExponentialDecay {
+    fn learning_rate(&self, base_lr: Dtype, step: usize) -> Dtype {
+        base_lr * self.gamma.powi(step as i32)
+    }
+}
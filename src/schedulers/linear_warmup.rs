@@ -0,0 +1,38 @@
+use crate::{Dtype, schedulers::LrScheduler};
+
+/// Ramps the learning rate linearly from `base_lr / warmup_steps` up to `base_lr`.
+///
+/// After the warmup the learning rate stays at `base_lr`, or follows the scheduler given to
+/// `then`, whose steps are counted from the end of the warmup.
+pub struct LinearWarmup {
+    pub warmup_steps: usize,
+    after: Option<Box<dyn LrScheduler>>,
+}
+
+impl LinearWarmup {
+    pub fn new(warmup_steps: usize) -> Self {
+        LinearWarmup {
+            warmup_steps,
+            after: None,
+        }
+    }
+
+    /// Continues with `scheduler` once the warmup is over.
+    pub fn then<S: LrScheduler + 'static>(mut self, scheduler: S) -> Self {
+        self.after = Some(Box::new(scheduler));
+        self
+    }
+}
+
+impl LrScheduler for LinearWarmup {
+    fn learning_rate(&self, base_lr: Dtype, step: usize) -> Dtype {
+        if step < self.warmup_steps {
+            return base_lr * (step + 1) as Dtype / self.warmup_steps as Dtype;
+        }
+
+        match &self.after {
+            Some(scheduler) => scheduler.learning_rate(base_lr, step - self.warmup_steps),
+            None => base_lr,
+        }
+    }
+}
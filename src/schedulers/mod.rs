@@ -0,0 +1,23 @@
+use crate::Dtype;
+
+pub mod cosine_annealing;
+pub mod exponential_decay;
+pub mod linear_warmup;
+pub mod one_cycle;
+pub mod scheduler_tests;
+pub mod step_decay;
+
+/// How often `Network::train` advances a learning-rate schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleInterval {
+    /// After every optimizer step (mini-batch).
+    Step,
+    /// At the start of every epoch.
+    Epoch,
+}
+
+pub trait LrScheduler: Send {
+    /// Returns the learning rate for the given step (0-based), where `base_lr` is the
+    /// learning rate the optimizer had when training started.
+    fn learning_rate(&self, base_lr: Dtype, step: usize) -> Dtype;
+}
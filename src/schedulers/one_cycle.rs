@@ -0,0 +1,52 @@
+use crate::{Dtype, schedulers::LrScheduler};
+
+const PI: Dtype = core::f64::consts::PI as Dtype;
+
+/// The 1cycle policy (Smith & Topin, "Super-Convergence").
+///
+/// The learning rate rises from `max_lr / div_factor` to `max_lr` during the first
+/// `pct_start` of `total_steps`, then anneals down to `max_lr / (div_factor * final_div_factor)`.
+/// Both phases use cosine interpolation. The optimizer's own learning rate is ignored.
+pub struct OneCycle {
+    pub max_lr: Dtype,
+    pub total_steps: usize,
+    pub pct_start: Dtype,
+    pub div_factor: Dtype,
+    pub final_div_factor: Dtype,
+}
+
+impl OneCycle {
+    /// Uses the common defaults `pct_start = 0.3`, `div_factor = 25` and `final_div_factor = 1e4`.
+    pub fn new(max_lr: Dtype, total_steps: usize) -> Self {
+        assert!(total_steps > 1, "total_steps must be greater than 1");
+        OneCycle {
+            max_lr,
+            total_steps,
+            pct_start: 0.3,
+            div_factor: 25.0,
+            final_div_factor: 1e4,
+        }
+    }
+}
+
+fn cosine_interpolate(start: Dtype, end: Dtype, progress: Dtype) -> Dtype {
+    end + (start - end) * (1.0 + (PI * progress).cos()) / 2.0
+}
+
+impl LrScheduler for OneCycle {
+    fn learning_rate(&self, _base_lr: Dtype, step: usize) -> Dtype {
+        let initial_lr = self.max_lr / self.div_factor;
+        let final_lr = initial_lr / self.final_div_factor;
+
+        let last_step = (self.total_steps - 1) as Dtype;
+        let step = (step as Dtype).min(last_step);
+        let peak_step = (self.pct_start * last_step).max(1.0);
+
+        if step <= peak_step {
+            cosine_interpolate(initial_lr, self.max_lr, step / peak_step)
+        } else {
+            let progress = (step - peak_step) / (last_step - peak_step).max(1.0);
+            cosine_interpolate(self.max_lr, final_lr, progress)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Dtype,
+        schedulers::{
+            LrScheduler, cosine_annealing::CosineAnnealingWarmRestarts,
+            exponential_decay::ExponentialDecay, linear_warmup::LinearWarmup, one_cycle::OneCycle,
+            step_decay::StepDecay,
+        },
+    };
+
+    fn assert_close(a: Dtype, b: Dtype) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_step_decay() {
+        let s = StepDecay::new(2, 0.5);
+        assert_close(s.learning_rate(1.0, 0), 1.0);
+        assert_close(s.learning_rate(1.0, 1), 1.0);
+        assert_close(s.learning_rate(1.0, 2), 0.5);
+        assert_close(s.learning_rate(1.0, 5), 0.25);
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        let s = ExponentialDecay::new(0.9);
+        assert_close(s.learning_rate(0.1, 0), 0.1);
+        assert_close(s.learning_rate(0.1, 2), 0.1 * 0.81);
+    }
+
+    #[test]
+    fn test_cosine_annealing_restarts() {
+        let s = CosineAnnealingWarmRestarts::new(4, 2, 0.0);
+        assert_close(s.learning_rate(1.0, 0), 1.0);
+        assert_close(s.learning_rate(1.0, 2), 0.5);
+        // First restart after 4 steps, second period is 8 steps long.
+        assert_close(s.learning_rate(1.0, 4), 1.0);
+        assert_close(s.learning_rate(1.0, 8), 0.5);
+        assert_close(s.learning_rate(1.0, 12), 1.0);
+    }
+
+    #[test]
+    fn test_linear_warmup_then_decay() {
+        let s = LinearWarmup::new(4).then(StepDecay::new(1, 0.5));
+        assert_close(s.learning_rate(1.0, 0), 0.25);
+        assert_close(s.learning_rate(1.0, 3), 1.0);
+        assert_close(s.learning_rate(1.0, 4), 1.0);
+        assert_close(s.learning_rate(1.0, 5), 0.5);
+    }
+
+    #[test]
+    fn test_one_cycle() {
+        let s = OneCycle::new(1.0, 11);
+        assert_close(s.learning_rate(0.0, 0), 1.0 / 25.0);
+        assert_close(s.learning_rate(0.0, 3), 1.0);
+        assert_close(s.learning_rate(0.0, 10), 1.0 / 25.0 / 1e4);
+        assert!(s.learning_rate(0.0, 6) < 1.0);
+    }
+}
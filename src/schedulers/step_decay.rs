@@ -0,0 +1,20 @@
+use crate::{Dtype, schedulers::LrScheduler};
+
+/// Multiplies the learning rate by `gamma` every `step_size` steps.
+pub struct StepDecay {
+    pub step_size: usize,
+    pub gamma: Dtype,
+}
+
+impl StepDecay {
+    pub fn new(step_size: usize, gamma: Dtype) -> Self {
+        assert!(step_size > 0, "step_size must be positive");
+        StepDecay { step_size, gamma }
+    }
+}
+
+impl LrScheduler for StepDecay {
+    fn learning_rate(&self, base_lr: Dtype, step: usize) -> Dtype {
+        base_lr * self.gamma.powi((step / self.step_size) as i32)
+    }
+}
@@ -1,25 +1,28 @@
 
-use crate::{
+use neural_networks::{
     Dtype,
     networks::network::Network,
-    training::data_load::load_data,
+    data::load::load_data,
+};
+
+use crate::{
+    cli::paths::Paths,
+    training::{TEST_LABELS, TEST_VECTORS},
 };
 
 // --- MNIST Training Function (Placeholder Architecture) ---
-pub fn test_network(net: &mut Network, input: usize, output: usize) -> anyhow::Result<(Dtype, Dtype)> {
+pub fn test_network(net: &mut Network, paths: &Paths, input: usize, output: usize) -> anyhow::Result<(Dtype, Dtype)> {
     log::info!("\n==============================================");
     log::info!("--- TEST Neural Network Training ---");
     log::info!("==============================================");
 
     // --- 2. Load FASHION MNIST Data ---
-    let path_inputs =
-        std::fs::canonicalize("/home/xhatalc/pv021_project/data/fashion_mnist_test_vectors.csv")?;
-    let path_labels =
-        std::fs::canonicalize("/home/xhatalc/pv021_project/data/fashion_mnist_test_labels.csv")?;
-
-    let (mut x_train, y_train, x_valid, y_valid) = match load_data(
-        path_inputs.to_str().unwrap(),
-        path_labels.to_str().unwrap(),
+    let path_inputs = paths.data(TEST_VECTORS);
+    let path_labels = paths.data(TEST_LABELS);
+
+    let (mut x_train, y_train, x_valid, _y_valid) = match load_data(
+        &path_inputs.to_string_lossy(),
+        &path_labels.to_string_lossy(),
         input,
         output,
         0.0,
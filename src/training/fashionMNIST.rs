@@ -1,18 +1,19 @@
-use crate::{
+use neural_networks::{
     Dtype,
-    callbacks::{early_stopping::EarlyStopping, plotting_callback::PlottingCallback},
-    layers::{
-        dense::{ConfigDenseLayer, DenseLayer},
-        relu::ReLULayer,
-        softmax::Softmax,
-    },
-    networks::network::Network,
+    callbacks::plotting_callback::PlottingCallback,
+    data::load::load_data,
+    networks::spec::{ModelSpec, OptimizerSpec, TrainingSpec},
+};
+
+use crate::{
+    cli::paths::Paths,
     testing::test_net::test_network,
-    training::data_load::load_data,
+    training::{TRAIN_LABELS, TRAIN_VECTORS},
 };
 
 // --- MNIST Training Function (Placeholder Architecture) ---
-pub fn train_mnist() -> anyhow::Result<()> {
+/// Trains the Fashion MNIST network and writes the model files to the output directory.
+pub fn train_mnist(paths: &Paths) -> anyhow::Result<()> {
     log::info!("\n==============================================");
     log::info!("--- FASHION MNIST Neural Network Training ---");
     log::info!("==============================================");
@@ -30,19 +31,18 @@ pub fn train_mnist() -> anyhow::Result<()> {
     const LEARNING_RATE: Dtype = 0.001;
     const EPOCHS: usize = 30;
     const BATCH_SIZE: usize = 128;
-    const MOMENTUM_FACTOR: Dtype = 0.1;
     const WEIGHT_DECAY: Dtype = 0.00001;
     const VALIDATION_SPLIT: f32 = 0.2;
+    const MODEL_PATH: &str = "fashion_mnist_model.json";
+    const ONNX_PATH: &str = "fashion_mnist_model.onnx";
 
     // --- 2. Load FASHION MNIST Data ---
-    let path_inputs =
-        std::fs::canonicalize("/home/xhatalc/pv021_project/data/fashion_mnist_train_vectors.csv")?;
-    let path_labels =
-        std::fs::canonicalize("/home/xhatalc/pv021_project/data/fashion_mnist_train_labels.csv")?;
+    let path_inputs = paths.data(TRAIN_VECTORS);
+    let path_labels = paths.data(TRAIN_LABELS);
 
     let (mut x_train, y_train, mut x_valid, y_valid) = match load_data(
-        path_inputs.to_str().unwrap(),
-        path_labels.to_str().unwrap(),
+        &path_inputs.to_string_lossy(),
+        &path_labels.to_string_lossy(),
         INPUT_SIZE,
         OUTPUT_SIZE,
         VALIDATION_SPLIT,
@@ -60,29 +60,34 @@ pub fn train_mnist() -> anyhow::Result<()> {
     log::info!("dataset size: {}, {}", x_train.rows, x_train.cols);
     log::info!("validation size: {}, {}", x_valid.rows, x_valid.cols);
 
-    let config = ConfigDenseLayer {
-        learning_rate: LEARNING_RATE,
-        momentum_factor: MOMENTUM_FACTOR,
-        weight_decay: WEIGHT_DECAY,
-    };
-
-    let mut net = Network::new();
-    net.add_layer(DenseLayer::new(INPUT_SIZE, H1_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H1_SIZE, H2_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H2_SIZE, H3_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H3_SIZE, OUTPUT_SIZE, &config));
-    net.add_layer(Softmax::new());
-
-    net.add_callback(PlottingCallback::new("fashion_minst"));
-    // net.add_callback(EarlyStopping::new(10, 0.001, &x_valid, &y_valid));
+    let spec = ModelSpec::mlp(INPUT_SIZE, &[H1_SIZE, H2_SIZE, H3_SIZE], OUTPUT_SIZE)
+        .with_optimizer(OptimizerSpec::adam(LEARNING_RATE, WEIGHT_DECAY))
+        .with_training(TrainingSpec {
+            epochs: EPOCHS,
+            batch_size: BATCH_SIZE,
+            validation_split: VALIDATION_SPLIT,
+            ..TrainingSpec::default()
+        });
+    let mut net = spec.build()?;
+
+    net.add_callback(PlottingCallback::new(paths.plots(), "fashion_minst"));
+    // net.add_callback(EarlyStopping::new(10, 0.001));
     // net.add_callback(DebugCallback::new());
 
     log::info!("\n--- Starting Training for {} Epochs ---", EPOCHS);
 
-    net.train(&x_train, &y_train, EPOCHS, BATCH_SIZE)?;
+    let history = net.train(
+        &x_train,
+        &y_train,
+        Some((&x_valid, &y_valid)),
+        EPOCHS,
+        BATCH_SIZE,
+    )?;
+    if let Some(best) = history.best_epoch() {
+        log::info!("Best epoch: {:?}", best);
+    }
+    net.save(paths.output_dir.join(MODEL_PATH))?;
+    net.export_onnx(paths.output_dir.join(ONNX_PATH))?;
 
     let final_pred = net.forward(&x_valid.split_into_batches(BATCH_SIZE)[0]);
     log::info!("\nFinal Predictions (Should be close to targets):");
@@ -99,7 +104,7 @@ pub fn train_mnist() -> anyhow::Result<()> {
         println!("\n");
     }
 
-    test_network(&mut net, INPUT_SIZE, OUTPUT_SIZE)?;
+    test_network(&mut net, paths, INPUT_SIZE, OUTPUT_SIZE)?;
 
     Ok(())
 }
@@ -1,3 +1,11 @@
+#[allow(non_snake_case)]
 pub mod fashionMNIST;
 pub mod xor;
-pub mod data_load;
+
+// Dataset files, relative to the data root.
+pub const TRAIN_VECTORS: &str = "fashion_mnist_train_vectors.csv";
+pub const TRAIN_LABELS: &str = "fashion_mnist_train_labels.csv";
+pub const TEST_VECTORS: &str = "fashion_mnist_test_vectors.csv";
+pub const TEST_LABELS: &str = "fashion_mnist_test_labels.csv";
+pub const XOR_VECTORS: &str = "xor_4.csv";
+pub const XOR_LABELS: &str = "xor_4_labels.csv";
\ No newline at end of file
@@ -6,19 +6,19 @@
 // use ratatui::widgets::{Block, Borders, Gauge, Paragraph};
 
 // Assuming these are defined in your project:
-use crate::Dtype;
-use crate::callbacks::debug_callback::DebugCallback;
-use crate::callbacks::plotting_callback::PlottingCallback;
-use crate::layers::dense::DenseLayer;
-use crate::layers::relu::ReLULayer;
-use crate::layers::softmax::Softmax;
-use crate::networks::network::Network;
-use crate::training::data_load::load_data;
+use neural_networks::Dtype;
+use neural_networks::callbacks::debug_callback::DebugCallback;
+use neural_networks::callbacks::plotting_callback::PlottingCallback;
+use neural_networks::data::load::load_data;
+use neural_networks::networks::spec::{ModelSpec, OptimizerSpec, TrainingSpec};
+
+use crate::cli::paths::Paths;
+use crate::training::{XOR_LABELS, XOR_VECTORS};
 
 // NOTE: You must have the calculate_loss and calculate_accuracy methods
 // implemented on your Network struct for this to compile.
 
-pub fn train_xor() -> anyhow::Result<()> {
+pub fn train_xor(paths: &Paths) -> anyhow::Result<()> {
     log::info!("\n==============================================");
     log::info!("--- XOR Neural Network Training ---");
     log::info!("==============================================");
@@ -30,17 +30,15 @@ pub fn train_xor() -> anyhow::Result<()> {
     const LEARNING_RATE: Dtype = 0.01;
     const EPOCHS: usize = 50000;
     const BATCH_SIZE: usize = 15;
-    const MOMENTUM_FACTOR: Dtype = 0.09;
     // todo momentum
-    // todo learning rate schedule
 
     // --- 2. Load XOR Data ---
-    let path_inputs = std::fs::canonicalize("../../../data/xor_4.csv")?;
-    let path_labels = std::fs::canonicalize("../../../data/xor_4_labels.csv")?;
+    let path_inputs = paths.data(XOR_VECTORS);
+    let path_labels = paths.data(XOR_LABELS);
 
     let (input_x, y_true, x_valid, y_valid) = match load_data(
-        path_inputs.to_str().unwrap(),
-        path_labels.to_str().unwrap(),
+        &path_inputs.to_string_lossy(),
+        &path_labels.to_string_lossy(),
         INPUT_SIZE,
         OUTPUT_SIZE,
         0.1,
@@ -54,41 +52,17 @@ pub fn train_xor() -> anyhow::Result<()> {
     // log::info!("data: {:?}", input_x);
     // log::info!("labels: {:?}", y_true);
 
-    // --- 3. Assemble the Network (2 -> 4 -> 2) ---
-    let config = crate::layers::dense::ConfigDenseLayer {
-        learning_rate: LEARNING_RATE,
-        momentum_factor: MOMENTUM_FACTOR,
-        weight_decay: 0.0,
-    };
-    let mut net = Network::new();
-    net.add_layer(DenseLayer::new(INPUT_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, H_SIZE, &config));
-    net.add_layer(ReLULayer::new());
-    net.add_layer(DenseLayer::new(H_SIZE, OUTPUT_SIZE, &config));
-    net.add_layer(Softmax::new());
+    // --- 3. Assemble the Network (4 -> 12 x 10 -> 2) ---
+    let spec = ModelSpec::mlp(INPUT_SIZE, &[H_SIZE; 12], OUTPUT_SIZE)
+        .with_optimizer(OptimizerSpec::adam(LEARNING_RATE, 0.0))
+        .with_training(TrainingSpec {
+            epochs: EPOCHS,
+            batch_size: BATCH_SIZE,
+            ..TrainingSpec::default()
+        });
+    let mut net = spec.build()?;
 
-    net.add_callback(PlottingCallback::new("./training_plot.png"));
+    net.add_callback(PlottingCallback::new(paths.plots(), "xor_"));
     net.add_callback(DebugCallback::new());
 
     log::info!("\n--- Starting Training for {} Epochs ---", EPOCHS);
@@ -96,6 +70,7 @@ pub fn train_xor() -> anyhow::Result<()> {
     net.train(
         &input_x,
         &y_true,
+        Some((&x_valid, &y_valid)),
         EPOCHS,
         BATCH_SIZE,
     )?;